@@ -1,19 +1,25 @@
 defmodule ElixirLanceDB.Native do
   alias ElixirLanceDB.Native.Schema.Infer
-  alias ElixirLanceDB.Native.Schema.NewColumnTransform.AllNulls
+  alias ElixirLanceDB.Native.Schema.NewColumnTransform
   alias ElixirLanceDB.Native.Schema.ColumnAlteration
   alias ElixirLanceDB.Native.Table.OptimizeAction.All
   alias ElixirLanceDB.Native.Table.FullTextSearchQueryRequest
   alias ElixirLanceDB.Native.Table.Index.{Auto, BTree, Bitmap, LabelList, IvfPq, FTS}
+  alias ElixirLanceDB.Native.Table.Index.FTS.TokenizerConfig
+  import ElixirLanceDB.Native.Table.Filter, only: [is_filter: 1]
 
   alias ElixirLanceDB.Native.Table.{
+    AddConfig,
+    CreateConfig,
     MergeInsertConfig,
     UpdateConfig,
     QueryRequest,
+    Reranker,
+    SearchByExample,
     VectorQueryRequest
   }
 
-  alias ElixirLanceDB.Native.Schema
+  alias ElixirLanceDB.Native.{CallbackServer, Schema, TableNamesRequest}
   # use Rustler, otp_app: :elixir_lancedb, crate: "elixir_lancedb"
 
   @version "0.1.1"
@@ -28,19 +34,33 @@ defmodule ElixirLanceDB.Native do
 
   def close_db_connection(conn) when is_reference(conn), do: err()
 
-  def table_names(conn) when is_reference(conn), do: err()
+  def table_names(conn, %TableNamesRequest{} \\ %TableNamesRequest{}) when is_reference(conn),
+    do: err()
+
+  @doc """
+  Like `table_names/2`, but returns a summary map per table with its `:name`,
+  `:row_count`, current `:version`, schema `:fields` and `:indices` names.
+  """
+  def list_tables(conn, %TableNamesRequest{} \\ %TableNamesRequest{}) when is_reference(conn),
+    do: err()
 
   def drop_all_tables(conn) when is_reference(conn),
     do: err()
 
   def drop_table(conn, table_name) when is_reference(conn) and is_binary(table_name), do: err()
 
-  def create_empty_table(conn, table_name, %Schema{})
+  def rename_table(conn, old_name, new_name)
+      when is_reference(conn) and is_binary(old_name) and is_binary(new_name),
+      do: err()
+
+  def table_exists(conn, table_name) when is_reference(conn) and is_binary(table_name), do: err()
+
+  def create_empty_table(conn, table_name, %Schema{}, %CreateConfig{} \\ %CreateConfig{})
       when is_binary(table_name) and is_reference(conn) do
     err()
   end
 
-  def create_table(conn, table_name, initial_data) do
+  def create_table(conn, table_name, initial_data, %CreateConfig{} = cfg \\ %CreateConfig{}) do
     {:ok, schema} = initial_data |> Schema.infer()
 
     initial_data =
@@ -49,44 +69,108 @@ defmodule ElixirLanceDB.Native do
         false -> initial_data
       end
 
-    create_table_with_data(conn, table_name, initial_data, schema)
+    create_table_with_data(conn, table_name, initial_data, schema, cfg)
   end
 
-  def create_table_with_data(_conn, _table_name, _initial_data, %Schema{}), do: err()
+  def create_table_with_data(
+        _conn,
+        _table_name,
+        _initial_data,
+        %Schema{},
+        %CreateConfig{} \\ %CreateConfig{}
+      ),
+      do: err()
 
   def open_table(_conn, _table_name), do: err()
 
   def close_table(_table_ref), do: err()
 
   def schema(_table_ref), do: err()
-  def add_columns(_table_ref, %AllNulls{transform_type: :all_nulls, schema: %Schema{}}), do: err()
+  def add_columns(table_ref, %NewColumnTransform.BatchUDF{pid: nil, fun: fun} = transform)
+      when is_function(fun, 1) do
+    CallbackServer.with_server(
+      fn -> NewColumnTransform.serve(fun) end,
+      &add_columns_with_transform(table_ref, %{transform | pid: &1})
+    )
+  end
+
+  def add_columns(table_ref, transform), do: add_columns_with_transform(table_ref, transform)
+
+  def add_columns_with_transform(_table_ref, %NewColumnTransform.AllNulls{schema: %Schema{}}),
+    do: err()
+
+  def add_columns_with_transform(_table_ref, %NewColumnTransform.SqlExpressions{}), do: err()
+  def add_columns_with_transform(_table_ref, %NewColumnTransform.Constant{}), do: err()
+  def add_columns_with_transform(_table_ref, %NewColumnTransform.BatchUDF{}), do: err()
+  def add_columns_reply(_reply_ref, _rows), do: err()
+  def add_columns_reply_error(_reply_ref, _message), do: err()
   def drop_columns(_table_ref, columns) when is_list(columns), do: err()
 
-  def alter_column(table_ref, %ColumnAlteration{} = col), do: alter_columns(table_ref, [col])
-  def alter_columns(_table_ref, [%ColumnAlteration{} | _rest]), do: err()
+  def alter_column(table_ref, %ColumnAlteration{} = col, mode \\ :commit),
+    do: alter_columns(table_ref, [col], mode)
 
-  def count_rows(_table_ref, filter \\ "") when is_binary(filter), do: err()
+  @doc """
+  With `:dry_run`, nothing is altered. Each cast is instead checked against the
+  current values and reported as `%{path, num_values, num_failed, num_overflowed}`.
+  `num_failed` values would make the cast fail. `num_overflowed` values are
+  numbers outside the target type's range; floats become infinite rather than fail.
+  """
+  def alter_columns(_table_ref, [%ColumnAlteration{} | _rest], mode \\ :commit)
+      when mode in [:commit, :dry_run],
+      do: err()
+
+  def count_rows(_table_ref, filter \\ "") when is_filter(filter), do: err()
 
   def optimize(_table_ref, %All{}), do: err()
 
   def query(_table_ref, %QueryRequest{} \\ %QueryRequest{}), do: err()
 
-  def add(table_ref, records) do
+  def add(table_ref, records, %AddConfig{} = cfg \\ %AddConfig{}) do
     records =
       case records |> Infer.needs_cleaning?() do
         true -> records |> Infer.clean()
         false -> records
       end
 
-    add_records(table_ref, records)
+    add_records(table_ref, records, cfg)
   end
 
-  def add_records(_table_ref, _records), do: err()
+  def add_records(_table_ref, _records, %AddConfig{} \\ %AddConfig{}), do: err()
 
+  @doc """
+  Returns a map of `:num_updated_rows` and the new table `:version`. With
+  `returning` set to `:row_ids` or `:rows`, the updated rows are read just
+  before the update commits, with their old values, and returned under that
+  key. Only local tables can return them.
+  """
   def update(_table_ref, %UpdateConfig{} = _update_cfg), do: err()
 
-  def delete(_table_ref, predicate) when is_binary(predicate), do: err()
-
+  @doc """
+  Returns a map of `:num_deleted_rows` and the new table `:version`. With
+  `returning` set to `:row_ids` or `:rows`, the deleted rows are read just
+  before the delete commits and returned under that key. Only local tables can
+  return them.
+
+  Lance does not report how many rows a delete removes, so they are counted
+  just before it. On remote tables that count is a separate request, which a
+  concurrent write may make stale.
+  """
+  def delete(_table_ref, predicate, returning \\ :count)
+      when is_filter(predicate) and returning in [:count, :row_ids, :rows],
+      do: err()
+
+  @doc """
+  Plans `filter` against the table schema without running it. Returns
+  `{:ok, []}` for a valid filter, or one issue per problem, each a map of
+  `:columns`, `:reason` (`:syntax`, `:unknown_column` or `:type_mismatch`)
+  and `:message`.
+  """
+  def validate_filter(_table_ref, filter) when is_filter(filter), do: err()
+
+  @doc """
+  Returns a map of `:num_inserted_rows`, `:num_updated_rows`, `:num_deleted_rows`
+  and the table `:version` after the merge.
+  """
   def merge_insert(_table_ref, _records, %MergeInsertConfig{} \\ %MergeInsertConfig{}), do: err()
 
   def list_indices(_table_ref), do: err()
@@ -99,11 +183,37 @@ defmodule ElixirLanceDB.Native do
   def create_index(_table_ref, fields, %IvfPq{}) when is_list(fields), do: err()
   def create_index(_table_ref, fields, %FTS{}) when is_list(fields), do: err()
 
+  def preview_tokenization(%TokenizerConfig{}, text) when is_binary(text), do: err()
+
   def full_text_search(_table_ref, %QueryRequest{full_text_search: %FullTextSearchQueryRequest{}}),
     do: err()
 
-  def vector_search(_table_ref, %VectorQueryRequest{}), do: err()
-  def hybrid_search(_table_ref, %VectorQueryRequest{}), do: err()
+  def parse_fts_query(query, default_columns \\ [])
+      when is_binary(query) and is_list(default_columns),
+      do: err()
+
+  def vector_search(table_ref, %VectorQueryRequest{} = req),
+    do: Reranker.with_server(req, &vector_search_with_reranker(table_ref, &1))
+
+  def vector_search_with_reranker(_table_ref, %VectorQueryRequest{}), do: err()
+  def batch_vector_search(_table_ref, %VectorQueryRequest{}), do: err()
+
+  def search_by_example(_table_ref, %SearchByExample{}, %VectorQueryRequest{}), do: err()
+  def hybrid_search(table_ref, %VectorQueryRequest{} = req),
+    do: Reranker.with_server(req, &hybrid_search_with_reranker(table_ref, &1))
+
+  def hybrid_search_with_reranker(_table_ref, %VectorQueryRequest{}), do: err()
+  def rerank_reply(_reply_ref, _scores), do: err()
+  def rerank_reply_error(_reply_ref, _message), do: err()
+
+  def explain_plan(_table_ref, request, verbose \\ false)
+      when (is_struct(request, QueryRequest) or is_struct(request, VectorQueryRequest)) and
+             is_boolean(verbose),
+      do: err()
+
+  def analyze_plan(_table_ref, request)
+      when is_struct(request, QueryRequest) or is_struct(request, VectorQueryRequest),
+      do: err()
 
   def to_arrow(_records, _schema), do: err()
 
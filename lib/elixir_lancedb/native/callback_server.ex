@@ -0,0 +1,24 @@
+defmodule ElixirLanceDB.Native.CallbackServer do
+  @moduledoc false
+
+  # Serves the messages a native call sends to an Elixir function for as long as
+  # that call runs. The server is monitored rather than linked, and stopped once
+  # the call returns, so neither outlives or takes down the other.
+  def with_server(serve, call) when is_function(serve, 0) and is_function(call, 1) do
+    {pid, ref} = spawn_monitor(serve)
+
+    try do
+      call.(pid)
+    after
+      Process.demonitor(ref, [:flush])
+      Process.exit(pid, :kill)
+    end
+  end
+
+  # Calls `fun`, turning anything it raises, throws or exits with into an error
+  def safe_apply(fun, args) do
+    {:ok, apply(fun, args)}
+  catch
+    kind, reason -> {:error, Exception.format_banner(kind, reason, __STACKTRACE__)}
+  end
+end
@@ -2,6 +2,7 @@ defmodule ElixirLanceDB.Native.Schema.ColumnAlteration do
   defstruct [:path, rename: nil, nullable: nil, data_type: nil]
 
   @type t() :: %__MODULE__{
+          # dotted for nested struct fields, which can be renamed or made nullable but not cast
           path: String.t(),
           rename: String.t() | nil,
           nullable: boolean() | nil,
@@ -1,4 +1,6 @@
 defmodule ElixirLanceDB.Native.Schema.Field do
+  import Kernel, except: [struct: 2]
+
   @derive Jason.Encoder
   defstruct ~w(name field_type nullable)a
 
@@ -37,14 +39,71 @@ defmodule ElixirLanceDB.Native.Schema.Field do
     new(name, :utf8, opts)
   end
 
+  def large_utf8(name, opts \\ []) do
+    new(name, :large_utf8, opts)
+  end
+
+  def binary(name, opts \\ []) do
+    new(name, :binary, opts)
+  end
+
+  def large_binary(name, opts \\ []) do
+    new(name, :large_binary, opts)
+  end
+
   def float32(name, opts \\ []) do
     new(name, :float32, opts)
   end
 
+  def float16(name, opts \\ []) do
+    new(name, :float16, opts)
+  end
+
+  def float64(name, opts \\ []) do
+    new(name, :float64, opts)
+  end
+
+  @doc """
+  As a vector child, each byte packs 8 bits of a binary vector, for use with
+  hamming distance.
+  """
+  def uint8(name, opts \\ []) do
+    new(name, :uint8, opts)
+  end
+
+  def uint16(name, opts \\ []) do
+    new(name, :uint16, opts)
+  end
+
+  def uint32(name, opts \\ []) do
+    new(name, :uint32, opts)
+  end
+
+  def uint64(name, opts \\ []) do
+    new(name, :uint64, opts)
+  end
+
+  def int8(name, opts \\ []) do
+    new(name, :int8, opts)
+  end
+
+  def int16(name, opts \\ []) do
+    new(name, :int16, opts)
+  end
+
   def int32(name, opts \\ []) do
     new(name, :int32, opts)
   end
 
+  def int64(name, opts \\ []) do
+    new(name, :int64, opts)
+  end
+
+  def decimal128(name, precision, scale, opts \\ [])
+      when is_integer(precision) and is_integer(scale) do
+    new(name, {:decimal128, precision, scale}, opts)
+  end
+
   def list(name, %__MODULE__{} = child, opts \\ []) do
     new(name, {:list, child}, opts)
   end
@@ -57,6 +116,15 @@ defmodule ElixirLanceDB.Native.Schema.Field do
     new(name, {:fixed_size_list, child, dimension}, opts)
   end
 
+  @doc """
+  A list of `dimension`-sized vectors per row, e.g. one embedding per token.
+  Searched with MaxSim by passing a list of vectors as the query vector.
+  """
+  def multivector(name, %__MODULE__{} = child, dimension, opts \\ [])
+      when is_integer(dimension) do
+    new(name, {:multi_vector, child, dimension}, opts)
+  end
+
   def date32(name, opts \\ []) do
     new(name, :date32, opts)
   end
@@ -64,4 +132,20 @@ defmodule ElixirLanceDB.Native.Schema.Field do
   def date64(name, opts \\ []) do
     new(name, :date64, opts)
   end
+
+  @doc """
+  A timestamp without a time zone, in `:second`, `:millisecond`, `:microsecond`
+  or `:nanosecond` units.
+  """
+  def timestamp(name, unit \\ :microsecond, opts \\ [])
+      when unit in [:second, :millisecond, :microsecond, :nanosecond] do
+    new(name, {:timestamp, unit}, opts)
+  end
+
+  @doc """
+  Nested fields are addressed by dotted paths, e.g. `"meta.score"`.
+  """
+  def struct(name, fields, opts \\ []) when is_list(fields) do
+    new(name, {:struct, fields}, opts)
+  end
 end
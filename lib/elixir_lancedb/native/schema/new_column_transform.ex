@@ -1,6 +1,12 @@
 defmodule ElixirLanceDB.Native.Schema.NewColumnTransform do
-  alias ElixirLanceDB.Native.Schema.NewColumnTransform.AllNulls
-  alias ElixirLanceDB.Native.Schema
+  alias ElixirLanceDB.Native.Schema.NewColumnTransform.{
+    AllNulls,
+    SqlExpressions,
+    Constant,
+    BatchUDF
+  }
+
+  alias ElixirLanceDB.Native.{CallbackServer, Schema}
 
   defmodule AllNulls do
     alias ElixirLanceDB.Native.Schema
@@ -12,9 +18,133 @@ defmodule ElixirLanceDB.Native.Schema.NewColumnTransform do
           }
   end
 
+  defmodule SqlExpressions do
+    defstruct expressions: [], transform_type: :sql_expressions
+
+    @type t() :: %__MODULE__{
+            transform_type: :sql_expressions,
+            # {column name, SQL expression over the existing columns}
+            expressions: list({String.t(), String.t()})
+          }
+  end
+
+  defmodule Constant do
+    alias ElixirLanceDB.Native.Table.Filter
+    defstruct [:name, :field_type, :value, transform_type: :constant]
+
+    @type t() :: %__MODULE__{
+            transform_type: :constant,
+            name: String.t(),
+            # one of :boolean, :utf8, :binary, :float32, :float64, :int8, :int16,
+            # :int32, :int64, :uint8, :uint16, :uint32, :uint64, :date32,
+            # {:decimal128, precision, scale} or {:timestamp, unit}
+            field_type: atom() | tuple(),
+            value: Filter.value()
+          }
+  end
+
+  defmodule BatchUDF do
+    alias ElixirLanceDB.Native.Schema
+
+    defstruct [
+      :pid,
+      :output_schema,
+      fun: nil,
+      input_columns: nil,
+      checkpoint_dir: nil,
+      timeout: 30_000,
+      transform_type: :batch_udf
+    ]
+
+    @type t() :: %__MODULE__{
+            transform_type: :batch_udf,
+            # nil while a function serves the batches, until add_columns/2 starts it
+            pid: pid() | nil,
+            fun: (list(map()) -> list(map())) | nil,
+            output_schema: Schema.t(),
+            # all columns are read when nil
+            input_columns: list(String.t()) | nil,
+            # finished batches are kept here until the new columns are committed
+            checkpoint_dir: String.t() | nil,
+            timeout: non_neg_integer()
+          }
+  end
+
+  @type t() :: AllNulls.t() | SqlExpressions.t() | Constant.t() | BatchUDF.t()
+
   def all_nulls(fields, %{} = metadata \\ %{}) when is_list(fields) do
     %AllNulls{
       schema: Schema.from(fields, metadata)
     }
   end
+
+  @doc """
+  Computes new columns from SQL expressions over the existing ones, e.g.
+  `sql_expressions([{"title_lower", "lower(title)"}])` or a map of the same.
+  """
+  def sql_expressions(expressions) when is_list(expressions) or is_map(expressions) do
+    %SqlExpressions{expressions: Enum.to_list(expressions)}
+  end
+
+  def constant(name, field_type, value)
+      when is_binary(name) and (is_atom(field_type) or is_tuple(field_type)) do
+    %Constant{name: name, field_type: field_type, value: value}
+  end
+
+  @doc """
+  Backfills new columns from an Elixir process, one batch of rows at a time.
+
+  The process receives `{:elixir_lancedb_add_columns, reply, rows}` and must
+  answer with `reply/2` within `:timeout` milliseconds, giving one row of the
+  new `fields` per input row, in the order received. Pass a 1-arity function
+  instead of a pid to have `add_columns/2` call it with `rows` from a process
+  that lives as long as the backfill. Anything the function raises fails the
+  backfill with an error rather than crashing the caller.
+
+  Options are `:input_columns` to limit the columns read, `:timeout`, and
+  `:checkpoint_dir`, a local directory where finished batches are kept so that
+  an interrupted backfill resumes when `add_columns/2` is called again with the
+  same transform. The checkpoints are removed once the columns are committed.
+
+  The process must not be the one calling `add_columns/2`, as that call blocks
+  until the backfill finishes.
+  """
+  def batch_udf(pid_or_fun, fields, opts \\ [])
+
+  def batch_udf(pid_or_fun, fields, opts)
+      when (is_pid(pid_or_fun) or is_function(pid_or_fun, 1)) and is_list(fields) and
+             is_list(opts) do
+    {pid, fun} = if is_pid(pid_or_fun), do: {pid_or_fun, nil}, else: {nil, pid_or_fun}
+
+    %BatchUDF{
+      pid: pid,
+      fun: fun,
+      output_schema: Schema.from(fields),
+      input_columns: Keyword.get(opts, :input_columns, nil),
+      checkpoint_dir: Keyword.get(opts, :checkpoint_dir, nil),
+      timeout: Keyword.get(opts, :timeout, 30_000)
+    }
+  end
+
+  def reply(reply, {:error, message}) when is_binary(message) do
+    ElixirLanceDB.Native.add_columns_reply_error(reply, message)
+  end
+
+  def reply(reply, rows) when is_list(rows) do
+    ElixirLanceDB.Native.add_columns_reply(reply, rows)
+  end
+
+  @doc false
+  def serve(fun) do
+    receive do
+      {:elixir_lancedb_add_columns, reply, rows} ->
+        case CallbackServer.safe_apply(fun, [rows]) do
+          {:ok, rows} when is_list(rows) -> reply(reply, rows)
+          {:ok, other} -> reply(reply, {:error, "expected a list of rows: #{inspect(other)}"})
+          {:error, message} -> reply(reply, {:error, message})
+        end
+
+        serve(fun)
+    end
+  end
 end
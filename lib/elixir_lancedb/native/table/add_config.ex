@@ -0,0 +1,26 @@
+defmodule ElixirLanceDB.Native.Table.AddConfig do
+  defstruct mode: :append,
+            max_rows_per_file: nil,
+            max_rows_per_group: nil,
+            data_storage_version: nil
+
+  @type t() :: %__MODULE__{
+          # :overwrite replaces the table contents in a single new version,
+          # keeping earlier versions in the table history. Lance drops indices
+          # on overwrite, so they need to be created again afterwards.
+          mode: :append | :overwrite,
+          max_rows_per_file: pos_integer() | nil,
+          max_rows_per_group: pos_integer() | nil,
+          # "legacy", "stable", "next", "2.0" or "2.1"
+          data_storage_version: String.t() | nil
+        }
+
+  def new(opts \\ []) when is_list(opts) do
+    %__MODULE__{
+      mode: Keyword.get(opts, :mode, :append),
+      max_rows_per_file: Keyword.get(opts, :max_rows_per_file, nil),
+      max_rows_per_group: Keyword.get(opts, :max_rows_per_group, nil),
+      data_storage_version: Keyword.get(opts, :data_storage_version, nil)
+    }
+  end
+end
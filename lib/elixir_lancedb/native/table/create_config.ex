@@ -0,0 +1,17 @@
+defmodule ElixirLanceDB.Native.Table.CreateConfig do
+  defstruct mode: :create, storage_options: %{}
+
+  @type t() :: %__MODULE__{
+          # :create fails when the name is taken, :overwrite replaces the
+          # existing table and :exist_ok opens it as long as its schema matches
+          mode: :create | :overwrite | :exist_ok,
+          storage_options: %{String.t() => String.t()}
+        }
+
+  def new(opts \\ []) when is_list(opts) do
+    %__MODULE__{
+      mode: Keyword.get(opts, :mode, :create),
+      storage_options: Keyword.get(opts, :storage_options, %{})
+    }
+  end
+end
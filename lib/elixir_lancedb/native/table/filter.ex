@@ -0,0 +1,40 @@
+defmodule ElixirLanceDB.Native.Table.Filter do
+  defstruct sql: "", params: [], named_params: %{}
+
+  @type value() ::
+          nil | boolean() | number() | String.t() | Date.t() | NaiveDateTime.t() | DateTime.t()
+
+  @type t() :: %__MODULE__{
+          sql: String.t(),
+          # bound in order to `?` placeholders
+          params: list(value() | list(value())),
+          # bound by name to `$name` placeholders
+          named_params: %{String.t() => value() | list(value())}
+        }
+
+  @doc """
+  Builds a filter from SQL with `?` placeholders bound to a list of values, or
+  `$name` placeholders bound to a map or keyword list. Values are rendered as
+  escaped SQL literals natively; a list becomes a parenthesized list for `IN`.
+
+      Filter.new("category = ? AND price < ?", ["books", 20])
+      Filter.new("id IN $ids AND published >= $since", ids: [1, 2], since: ~D[2024-01-01])
+  """
+  def new(sql, bindings \\ [])
+
+  def new(sql, bindings) when is_binary(sql) and is_map(bindings) do
+    %__MODULE__{
+      sql: sql,
+      named_params: Map.new(bindings, fn {name, value} -> {to_string(name), value} end)
+    }
+  end
+
+  def new(sql, bindings) when is_binary(sql) and is_list(bindings) do
+    case bindings != [] and Keyword.keyword?(bindings) do
+      true -> new(sql, Map.new(bindings))
+      false -> %__MODULE__{sql: sql, params: bindings}
+    end
+  end
+
+  defguard is_filter(filter) when is_binary(filter) or is_struct(filter, __MODULE__)
+end
@@ -0,0 +1,152 @@
+defmodule ElixirLanceDB.Native.Table.FtsQuery do
+  alias ElixirLanceDB.Native.Table.FtsQuery.{Match, Phrase, MultiMatch, Boolean, Boost}
+
+  @type t() :: Match.t() | Phrase.t() | MultiMatch.t() | Boolean.t() | Boost.t()
+  @type operator() :: :and | :or
+
+  defmodule Match do
+    defstruct [
+      :terms,
+      query_type: :match,
+      column: nil,
+      boost: 1.0,
+      fuzziness: 0,
+      max_expansions: 50,
+      operator: :or
+    ]
+
+    @type t() :: %__MODULE__{
+            query_type: :match,
+            terms: String.t(),
+            column: String.t() | nil,
+            boost: float(),
+            # nil lets Lance pick the edit distance from the term length
+            fuzziness: non_neg_integer() | nil,
+            max_expansions: non_neg_integer(),
+            operator: ElixirLanceDB.Native.Table.FtsQuery.operator()
+          }
+  end
+
+  def match(terms, opts \\ []) when is_binary(terms) and is_list(opts) do
+    %Match{
+      terms: terms,
+      column: get(opts, :column),
+      boost: get(opts, :boost, 1.0),
+      fuzziness: get(opts, :fuzziness, 0),
+      max_expansions: get(opts, :max_expansions, 50),
+      operator: get(opts, :operator, :or)
+    }
+  end
+
+  defmodule Phrase do
+    defstruct [:terms, query_type: :phrase, column: nil, slop: 0]
+
+    @type t() :: %__MODULE__{
+            query_type: :phrase,
+            terms: String.t(),
+            column: String.t() | nil,
+            slop: non_neg_integer()
+          }
+  end
+
+  def phrase(terms, opts \\ []) when is_binary(terms) and is_list(opts) do
+    %Phrase{
+      terms: terms,
+      column: get(opts, :column),
+      slop: get(opts, :slop, 0)
+    }
+  end
+
+  defmodule MultiMatch do
+    defstruct [:terms, query_type: :multi_match, columns: [], boosts: nil, operator: :or]
+
+    @type t() :: %__MODULE__{
+            query_type: :multi_match,
+            terms: String.t(),
+            columns: list(String.t()),
+            boosts: list(float()) | nil,
+            operator: ElixirLanceDB.Native.Table.FtsQuery.operator()
+          }
+  end
+
+  @doc """
+  Matches `terms` against several columns. Columns may be given as a list of names,
+  or as a keyword list / map of column name to boost.
+  """
+  def multi_match(terms, columns, opts \\ [])
+
+  def multi_match(terms, columns, opts) when is_map(columns) do
+    multi_match(terms, columns |> Map.to_list(), opts)
+  end
+
+  def multi_match(terms, [{_, _} | _] = columns, opts) when is_binary(terms) do
+    %MultiMatch{
+      terms: terms,
+      columns: columns |> Enum.map(fn {column, _} -> column |> to_string() end),
+      boosts: columns |> Enum.map(fn {_, boost} -> boost / 1 end),
+      operator: get(opts, :operator, :or)
+    }
+  end
+
+  def multi_match(terms, columns, opts)
+      when is_binary(terms) and is_list(columns) and length(columns) > 0 do
+    %MultiMatch{
+      terms: terms,
+      columns: columns,
+      boosts: get(opts, :boosts),
+      operator: get(opts, :operator, :or)
+    }
+  end
+
+  defmodule Boolean do
+    defstruct query_type: :boolean, must: [], should: [], must_not: []
+
+    @type t() :: %__MODULE__{
+            query_type: :boolean,
+            must: list(ElixirLanceDB.Native.Table.FtsQuery.t()),
+            should: list(ElixirLanceDB.Native.Table.FtsQuery.t()),
+            must_not: list(ElixirLanceDB.Native.Table.FtsQuery.t())
+          }
+  end
+
+  def boolean(opts \\ []) when is_list(opts) do
+    %Boolean{
+      must: get(opts, :must, []),
+      should: get(opts, :should, []),
+      must_not: get(opts, :must_not, [])
+    }
+  end
+
+  defmodule Boost do
+    defstruct [:positive, :negative, query_type: :boost, negative_boost: nil]
+
+    @type t() :: %__MODULE__{
+            query_type: :boost,
+            positive: ElixirLanceDB.Native.Table.FtsQuery.t(),
+            negative: ElixirLanceDB.Native.Table.FtsQuery.t(),
+            # nil uses the Lance default of 0.5
+            negative_boost: float() | nil
+          }
+  end
+
+  def boost(positive, negative, opts \\ []) when is_list(opts) do
+    %Boost{
+      positive: positive,
+      negative: negative,
+      negative_boost: get(opts, :negative_boost)
+    }
+  end
+
+  @doc """
+  Parses a Lucene-style query string such as `title:"vector db" AND -draft body:lance~1`.
+  Terms without a field are matched against `default_columns`. Returns
+  `{:error, {:fts_query_parse_error, %{message: message, position: byte_offset}}}` on bad input.
+  """
+  def parse(query, default_columns \\ []) when is_binary(query) and is_list(default_columns) do
+    ElixirLanceDB.Native.parse_fts_query(query, default_columns)
+  end
+
+  defp get(list, key, default \\ nil) do
+    list |> Keyword.get(key, default)
+  end
+end
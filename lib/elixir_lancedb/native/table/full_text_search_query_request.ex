@@ -1,19 +1,66 @@
 defmodule ElixirLanceDB.Native.Table.FullTextSearchQueryRequest do
-  defstruct [:query, limit: nil, columns: [], wand_factor: nil]
+  alias ElixirLanceDB.Native.Table.FtsQuery
+  alias ElixirLanceDB.Native.Table.FullTextSearchQueryRequest.Highlight
+
+  defstruct [:query, limit: nil, columns: [], wand_factor: nil, highlight: nil]
 
   @type t() :: %__MODULE__{
-          query: String.t(),
+          query: String.t() | FtsQuery.t(),
           limit: integer() | nil,
           columns: list(String.t()),
-          wand_factor: float() | nil
+          wand_factor: float() | nil,
+          highlight: Highlight.t() | nil
         }
 
-  def new(query, opts \\ []) when is_binary(query) and is_list(opts) do
+  defmodule Highlight do
+    defstruct [
+      columns: [],
+      pre_tag: "<em>",
+      post_tag: "</em>",
+      fragment_size: nil,
+      max_fragments: 3
+    ]
+
+    @type t() :: %__MODULE__{
+            columns: list(String.t()),
+            pre_tag: String.t(),
+            post_tag: String.t(),
+            # nil returns the whole column value as a single fragment
+            fragment_size: non_neg_integer() | nil,
+            max_fragments: non_neg_integer()
+          }
+
+    @doc """
+    Highlights matched terms in the returned rows under a `"_highlights"` key.
+
+    Terms are tokenized the way each column's full text search index was built,
+    so stemming and folding line up with what actually matched. Highlighting a
+    column without such an index fails the search.
+    """
+    def new(opts \\ []) when is_list(opts) do
+      %__MODULE__{
+        columns: Keyword.get(opts, :columns, []),
+        pre_tag: Keyword.get(opts, :pre_tag, "<em>"),
+        post_tag: Keyword.get(opts, :post_tag, "</em>"),
+        fragment_size: Keyword.get(opts, :fragment_size, nil),
+        max_fragments: Keyword.get(opts, :max_fragments, 3)
+      }
+    end
+  end
+
+  def new(query, opts \\ []) when (is_binary(query) or is_struct(query)) and is_list(opts) do
     %__MODULE__{
       query: query,
       limit: Keyword.get(opts, :limit, nil),
       columns: Keyword.get(opts, :columns, []),
-      wand_factor: Keyword.get(opts, :wand_factor, nil)
+      wand_factor: Keyword.get(opts, :wand_factor, nil),
+      highlight: opts |> Keyword.get(:highlight, nil) |> highlight()
     }
   end
+
+  defp highlight(nil), do: nil
+  defp highlight(false), do: nil
+  defp highlight(true), do: Highlight.new()
+  defp highlight(%Highlight{} = highlight), do: highlight
+  defp highlight(opts) when is_list(opts), do: Highlight.new(opts)
 end
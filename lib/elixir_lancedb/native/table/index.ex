@@ -58,10 +58,14 @@ defmodule ElixirLanceDB.Native.Table.Index do
                 lower_case: true,
                 stem: false,
                 remove_stop_words: false,
-                ascii_folding: false
+                stop_words: nil,
+                ascii_folding: false,
+                min_ngram_length: 3,
+                max_ngram_length: 3,
+                prefix_only: false
 
       @type t() :: %__MODULE__{
-              base_tokenizer: :simple | :whitespace | :raw | String.t(),
+              base_tokenizer: :simple | :whitespace | :raw | :ngram,
               language:
                 :arabic
                 | :danish
@@ -86,26 +90,47 @@ defmodule ElixirLanceDB.Native.Table.Index do
               lower_case: boolean(),
               stem: boolean(),
               remove_stop_words: boolean(),
-              ascii_folding: boolean()
+              # replaces the language's built-in stop word list
+              stop_words: list(String.t()) | nil,
+              ascii_folding: boolean(),
+              # only used by the :ngram base tokenizer
+              min_ngram_length: pos_integer(),
+              max_ngram_length: pos_integer(),
+              prefix_only: boolean()
             }
     end
   end
 
   def fts(config \\ []) do
+    stop_words = get(config, :stop_words)
+
     %FTS{
       with_position: get(config, :with_position, true),
       tokenizer_configs: %TokenizerConfig{
-        base_tokenizer: get(config, :base_tokenizer, :simple) |> Atom.to_string,
-        language: get(config, :language, :english) |> Atom.to_string,
+        base_tokenizer: get(config, :base_tokenizer, :simple),
+        language: get(config, :language, :english) |> to_string(),
         max_token_length: get(config, :max_token_length, 40),
         lower_case: get(config, :lower_case, true),
         stem: get(config, :stem, false),
-        remove_stop_words: get(config, :remove_stop_words, false),
-        ascii_folding: get(config, :ascii_folding, false)
+        remove_stop_words: get(config, :remove_stop_words, stop_words != nil),
+        stop_words: stop_words,
+        ascii_folding: get(config, :ascii_folding, false),
+        min_ngram_length: get(config, :min_ngram_length, 3),
+        max_ngram_length: get(config, :max_ngram_length, 3),
+        prefix_only: get(config, :prefix_only, false)
       }
     }
   end
 
+  @doc """
+  Runs `text` through the analyzer an FTS index built with `config` would use,
+  returning the tokens with their positions and byte offsets.
+  """
+  def preview_tokenization(text, config \\ []) when is_binary(text) and is_list(config) do
+    %FTS{tokenizer_configs: tokenizer_configs} = fts(config)
+    ElixirLanceDB.Native.preview_tokenization(tokenizer_configs, text)
+  end
+
   @type distance_type() :: :l2 | :cosine | :dot | :hamming
 
   defmodule IvfFlat do
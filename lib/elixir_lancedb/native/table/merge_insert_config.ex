@@ -1,17 +1,42 @@
 defmodule ElixirLanceDB.Native.Table.MergeInsertConfig do
+  alias ElixirLanceDB.Native.Table.Filter
+  alias ElixirLanceDB.Native.Table.UpdateConfig.ColumnOperation
+
   defstruct on: [],
             when_matched_update_all: false,
             when_matched_update_all_filt: nil,
+            when_matched_update_columns: [],
+            when_matched_update_columns_filt: nil,
             when_not_matched_insert_all: false,
             when_not_matched_by_source_delete: false,
-            when_not_matched_by_source_delete_filt: nil
+            when_not_matched_by_source_delete_filt: nil,
+            when_not_matched_by_source_update: [],
+            when_not_matched_by_source_update_filt: nil,
+            duplicate_keys: :reject
 
+  # Every merge commits a single table version. Column updates on matched rows and
+  # by-source updates combine with inserts, e.g. to insert new rows, update some
+  # columns of matched ones and mark missing ones as deleted, but they need a local
+  # table. Column updates can't be combined with update-all, by-source updates
+  # with by-source deletes, and the matched-row filters only apply to a clause that
+  # runs alone.
   @type t() :: %__MODULE__{
           on: list(String.t()),
           when_matched_update_all: boolean(),
-          when_matched_update_all_filt: String.t() | nil,
+          when_matched_update_all_filt: Filter.t() | String.t() | nil,
+          # copies only these source columns onto matched rows
+          when_matched_update_columns: list(String.t()),
+          # may refer to `source.` and `target.` columns, like the update-all filter
+          when_matched_update_columns_filt: Filter.t() | String.t() | nil,
           when_not_matched_insert_all: boolean(),
           when_not_matched_by_source_delete: boolean(),
-          when_not_matched_by_source_delete_filt: String.t() | nil
+          when_not_matched_by_source_delete_filt: Filter.t() | String.t() | nil,
+          # SQL updates applied to target rows whose key is not in the source,
+          # which must be unique and not null in the table
+          when_not_matched_by_source_update: list(ColumnOperation.t()),
+          when_not_matched_by_source_update_filt: Filter.t() | String.t() | nil,
+          # source rows sharing an `on` key are rejected with the offending keys,
+          # or reduced to the last of them
+          duplicate_keys: :reject | :last_write_wins
         }
 end
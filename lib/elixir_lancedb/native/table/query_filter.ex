@@ -1,4 +1,6 @@
 defmodule ElixirLanceDB.Native.Table.QueryFilter do
+  import ElixirLanceDB.Native.Table.Filter, only: [is_filter: 1]
+
   defstruct [
     :sql,
     # :substrait,
@@ -6,12 +8,12 @@ defmodule ElixirLanceDB.Native.Table.QueryFilter do
   ]
 
   @type t() :: %__MODULE__{
-          sql: String.t() | nil,
+          sql: ElixirLanceDB.Native.Table.Filter.t() | String.t() | nil,
           # substrait: integer() | nil,
           # datafusion: term() | nil
         }
 
-  def new(sql, opts \\ []) when is_binary(sql) and is_list(opts) do
+  def new(sql, opts \\ []) when is_filter(sql) and is_list(opts) do
     %__MODULE__{
       sql: sql
     }
@@ -2,15 +2,16 @@ defmodule ElixirLanceDB.Native.Table.QueryRequest do
   alias ElixirLanceDB.Native.Table.FullTextSearchQueryRequest
   alias ElixirLanceDB.Native.Table.FullTextSearchQuery
   alias ElixirLanceDB.Native.Table.QueryFilter
+  import ElixirLanceDB.Native.Table.Filter, only: [is_filter: 1]
 
   defstruct [
     :filter,
     limit: nil,
-    full_text_search: nil
-    # :offset,
-    # :select,
-    # :fast_search,
-    # :with_row_id,
+    full_text_search: nil,
+    select: nil,
+    offset: nil,
+    with_row_id: false,
+    fast_search: false
     # :prefilter,
     # :reranker,
     # :norm
@@ -19,11 +20,14 @@ defmodule ElixirLanceDB.Native.Table.QueryRequest do
   @type t() :: %__MODULE__{
           filter: QueryFilter.t() | map() | nil,
           limit: integer() | nil,
-          full_text_search: FullTextSearchQuery.t() | nil
-          # offset: integer() | nil,
-          # select: map() | nil,
-          # fast_search: boolean(),
-          # with_row_id: boolean(),
+          full_text_search: FullTextSearchQuery.t() | nil,
+          # column names, or alias => SQL expression
+          select: list(String.t()) | %{String.t() => String.t()} | nil,
+          offset: non_neg_integer() | nil,
+          # adds the `_rowid` column
+          with_row_id: boolean(),
+          # skips data not yet covered by an index
+          fast_search: boolean()
           # prefilter: boolean(),
           # reranker: map() | nil,
           # norm: map() | nil
@@ -37,7 +41,46 @@ defmodule ElixirLanceDB.Native.Table.QueryRequest do
     }
   end
 
-  def filter(%__MODULE__{} = request, sql, opts \\ []) when is_binary(sql) and is_list(opts) do
+  def offset(%__MODULE__{} = request, offset) when is_integer(offset) and offset >= 0 do
+    %__MODULE__{
+      request
+      | offset: offset
+    }
+  end
+
+  def with_row_id(%__MODULE__{} = request, with_row_id \\ true) when is_boolean(with_row_id) do
+    %__MODULE__{
+      request
+      | with_row_id: with_row_id
+    }
+  end
+
+  def fast_search(%__MODULE__{} = request, fast_search \\ true) when is_boolean(fast_search) do
+    %__MODULE__{
+      request
+      | fast_search: fast_search
+    }
+  end
+
+  @doc """
+  Restricts the returned columns, either to a list of column names or to a map
+  of output alias to SQL expression, e.g. `%{"title_lower" => "lower(title)"}`.
+  """
+  def select(%__MODULE__{} = request, columns) when is_list(columns) do
+    %__MODULE__{
+      request
+      | select: columns |> Enum.map(&to_string/1)
+    }
+  end
+
+  def select(%__MODULE__{} = request, expressions) when is_map(expressions) do
+    %__MODULE__{
+      request
+      | select: Map.new(expressions, fn {name, sql} -> {to_string(name), sql} end)
+    }
+  end
+
+  def filter(%__MODULE__{} = request, sql, opts \\ []) when is_filter(sql) and is_list(opts) do
     %__MODULE__{
       request
       | filter: QueryFilter.new(sql, opts)
@@ -59,4 +102,16 @@ defmodule ElixirLanceDB.Native.Table.QueryRequest do
       | full_text_search: FullTextSearchQueryRequest.new(query, opts)
     }
   end
+
+  @doc """
+  Sets a structured full text search query built with `ElixirLanceDB.Native.Table.FtsQuery`.
+  Columns are taken from the query nodes themselves.
+  """
+  def fts_query(%__MODULE__{} = request, query, opts \\ [])
+      when is_struct(query) and is_list(opts) do
+    %__MODULE__{
+      request
+      | full_text_search: FullTextSearchQueryRequest.new(query, opts |> Keyword.delete(:columns))
+    }
+  end
 end
@@ -0,0 +1,107 @@
+defmodule ElixirLanceDB.Native.Table.Reranker do
+  alias ElixirLanceDB.Native.CallbackServer
+
+  defmodule RRF do
+    defstruct reranker_type: :rrf, k: 60.0
+
+    @type t() :: %__MODULE__{reranker_type: :rrf, k: float()}
+  end
+
+  defmodule LinearCombination do
+    defstruct reranker_type: :linear_combination, vector_weight: 0.7, fts_weight: 0.3
+
+    @type t() :: %__MODULE__{
+            reranker_type: :linear_combination,
+            vector_weight: float(),
+            fts_weight: float()
+          }
+  end
+
+  defmodule Callback do
+    defstruct [:pid, fun: nil, reranker_type: :callback, timeout: 5_000]
+
+    @type t() :: %__MODULE__{
+            reranker_type: :callback,
+            # nil while a function scores the candidates, until the search starts it
+            pid: pid() | nil,
+            fun: (String.t(), list(map()) -> list(number())) | nil,
+            timeout: non_neg_integer()
+          }
+  end
+
+  @type t() :: RRF.t() | LinearCombination.t() | Callback.t()
+
+  @doc """
+  Reciprocal rank fusion: each leg contributes `1 / (rank + k)` per row.
+  """
+  def rrf(k \\ 60.0) when is_number(k) do
+    %RRF{k: k / 1}
+  end
+
+  @doc """
+  Weighted sum of the normalized vector similarity and full text search score.
+  """
+  def linear_combination(opts \\ []) when is_list(opts) do
+    %LinearCombination{
+      vector_weight: Keyword.get(opts, :vector_weight, 0.7) / 1,
+      fts_weight: Keyword.get(opts, :fts_weight, 0.3) / 1
+    }
+  end
+
+  @doc """
+  Hands candidates to an Elixir process for scoring, e.g. a cross-encoder.
+
+  The process receives `{:elixir_lancedb_rerank, reply, query, rows}` and must
+  answer with `reply/2` within `:timeout` milliseconds, giving one score per row
+  in the order received. Higher scores rank first. `query` is the full text
+  search text, and an empty string for `vector_search`, which has none.
+
+  Pass a 2-arity function instead of a pid to have it called with `query` and
+  `rows` by a process that lives only as long as each search. A function that
+  raises fails the search instead of the caller.
+
+  The process must not be the one running the search, as that call blocks
+  until the reply arrives.
+  """
+  def callback(pid_or_fun, opts \\ [])
+
+  def callback(fun, opts) when is_function(fun, 2) and is_list(opts) do
+    %Callback{fun: fun, timeout: Keyword.get(opts, :timeout, 5_000)}
+  end
+
+  def callback(pid, opts) when is_pid(pid) and is_list(opts) do
+    %Callback{pid: pid, timeout: Keyword.get(opts, :timeout, 5_000)}
+  end
+
+  def reply(reply, {:error, message}) when is_binary(message) do
+    ElixirLanceDB.Native.rerank_reply_error(reply, message)
+  end
+
+  def reply(reply, scores) when is_list(scores) do
+    ElixirLanceDB.Native.rerank_reply(reply, scores |> Enum.map(&(&1 / 1)))
+  end
+
+  @doc false
+  def with_server(%{reranker: %Callback{pid: nil, fun: fun} = reranker} = req, search)
+      when is_function(fun, 2) do
+    CallbackServer.with_server(
+      fn -> serve(fun) end,
+      &search.(%{req | reranker: %{reranker | pid: &1}})
+    )
+  end
+
+  def with_server(req, search), do: search.(req)
+
+  defp serve(fun) do
+    receive do
+      {:elixir_lancedb_rerank, reply, query, rows} ->
+        case CallbackServer.safe_apply(fun, [query, rows]) do
+          {:ok, scores} when is_list(scores) -> reply(reply, scores)
+          {:ok, other} -> reply(reply, {:error, "expected a list of scores: #{inspect(other)}"})
+          {:error, message} -> reply(reply, {:error, message})
+        end
+
+        serve(fun)
+    end
+  end
+end
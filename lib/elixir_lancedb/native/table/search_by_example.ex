@@ -0,0 +1,25 @@
+defmodule ElixirLanceDB.Native.Table.SearchByExample do
+  defstruct [:column, filter: nil, row_id: nil, exclude_source: true]
+
+  @type t() :: %__MODULE__{
+          column: String.t(),
+          # exactly one of filter or row_id, matching a single row
+          filter: ElixirLanceDB.Native.Table.Filter.t() | String.t() | nil,
+          row_id: non_neg_integer() | nil,
+          exclude_source: boolean()
+        }
+
+  @doc """
+  Uses the `column` vector of the row matched by `:filter` or `:row_id` as the
+  query vector. The source row is left out of the results unless
+  `exclude_source: false` is given.
+  """
+  def new(column, opts \\ []) when is_binary(column) and is_list(opts) do
+    %__MODULE__{
+      column: column,
+      filter: Keyword.get(opts, :filter, nil),
+      row_id: Keyword.get(opts, :row_id, nil),
+      exclude_source: Keyword.get(opts, :exclude_source, true)
+    }
+  end
+end
@@ -1,9 +1,14 @@
 defmodule ElixirLanceDB.Native.Table.UpdateConfig do
-  defstruct [:filter, columns: []]
+  alias ElixirLanceDB.Native.Table.Filter
+  import Filter, only: [is_filter: 1]
+
+  defstruct [:filter, columns: [], returning: :count]
 
   @type t() :: %__MODULE__{
           columns: list(ColumnOperation.t()),
-          filter: String.t() | nil
+          filter: Filter.t() | String.t() | nil,
+          # anything but :count also returns the rows as they were before the update
+          returning: :count | :row_ids | :rows
         }
 
   defmodule ColumnOperation do
@@ -26,7 +31,11 @@ defmodule ElixirLanceDB.Native.Table.UpdateConfig do
     }
   end
 
-  def filter(%__MODULE__{filter: nil} = cfg, filter) when is_binary(filter) do
+  def returning(%__MODULE__{} = cfg, returning) when returning in [:count, :row_ids, :rows] do
+    %__MODULE__{cfg | returning: returning}
+  end
+
+  def filter(%__MODULE__{filter: nil} = cfg, filter) when is_filter(filter) do
     %__MODULE__{
       cfg
       | filter: filter
@@ -1,18 +1,26 @@
 defmodule ElixirLanceDB.Native.Table.VectorQueryRequest do
   alias ElixirLanceDB.Native.Table.QueryRequest
+  alias ElixirLanceDB.Native.Table.Reranker
+  import ElixirLanceDB.Native.Table.Filter, only: [is_filter: 1]
 
   defstruct [
     :base,
     postfilter: false,
     column: nil,
     query_vector: [],
+    query_vectors: [],
+    vector_type: :float32,
     nprobes: 20,
     lower_bound: nil,
     upper_bound: nil,
     ef: nil,
     refine_factor: nil,
     distance_type: nil,
-    use_index: true
+    use_index: true,
+    reranker: %Reranker.RRF{},
+    norm: :score,
+    vector_limit: nil,
+    fts_limit: nil
   ]
 
   @type t() :: %__MODULE__{
@@ -22,22 +30,37 @@ defmodule ElixirLanceDB.Native.Table.VectorQueryRequest do
 
           # Vector
           column: String.t() | nil,
-          query_vector: list(float()),
+          # a list of vectors searches a multivector column with MaxSim
+          query_vector: list(float()) | list(list(float())),
+          # searched together by `batch_vector_search`, taking precedence over query_vector
+          query_vectors: list(list(float())),
+          # element type of the searched column; :uint8 vectors are packed bits
+          vector_type: :float16 | :float32 | :float64 | :uint8,
           nprobes: non_neg_integer() | nil,
           lower_bound: float() | nil,
           upper_bound: float() | nil,
           ef: non_neg_integer() | nil,
           refine_factor: non_neg_integer() | nil,
           distance_type: atom() | nil,
-          use_index: boolean()
+          use_index: boolean(),
+
+          # Hybrid
+          reranker: Reranker.t(),
+          norm: :score | :rank,
+          # candidates fetched from each leg before reranking, default to the base limit
+          vector_limit: non_neg_integer() | nil,
+          fts_limit: non_neg_integer() | nil
         }
 
   def new(query_vector, opts \\ []) when is_list(query_vector) and is_list(opts) do
     base_request = Keyword.get(opts, :base, %QueryRequest{})
 
+    vector_type = Keyword.get(opts, :vector_type, :float32)
+
     %__MODULE__{
       base: base_request,
-      query_vector: query_vector,
+      query_vector: query_vector |> cast_vector(vector_type),
+      vector_type: vector_type,
       column: Keyword.get(opts, :column, nil),
       postfilter: Keyword.get(opts, :postfilter, false),
       nprobes: Keyword.get(opts, :nprobes, 20),
@@ -50,20 +73,39 @@ defmodule ElixirLanceDB.Native.Table.VectorQueryRequest do
     }
   end
 
+  @doc """
+  Builds a request for `batch_vector_search`, which returns one result list per
+  query vector, in order. Accepts the same options as `new/2`.
+  """
+  def batch(query_vectors, opts \\ []) when is_list(query_vectors) and is_list(opts) do
+    req = new([], opts)
+
+    %__MODULE__{
+      req
+      | query_vectors: query_vectors |> Enum.map(&cast_vector(&1, req.vector_type))
+    }
+  end
+
   def with_vector(%__MODULE__{} = req, query_vector) when is_list(query_vector) do
     %__MODULE__{
       req
-      | query_vector: query_vector
+      | query_vector: query_vector |> cast_vector(req.vector_type)
     }
   end
 
-  def filter(%__MODULE__{} = req, filter, opts \\ []) when is_binary(filter) and is_list(opts) do
+  def filter(%__MODULE__{} = req, filter, opts \\ []) when is_filter(filter) and is_list(opts) do
     %__MODULE__{
       req
       | base: req.base |> QueryRequest.filter(filter, opts)
     }
   end
 
+  @doc """
+  Adds a full text search leg to the request for use with `hybrid_search`.
+
+  Accepts `:reranker`, `:norm`, `:vector_limit` and `:fts_limit` alongside the
+  full text search options.
+  """
   def hybridize(req, query, columns, opts \\ [])
 
   def hybridize(req, query, single_column, opts) when is_binary(single_column) do
@@ -72,9 +114,21 @@ defmodule ElixirLanceDB.Native.Table.VectorQueryRequest do
 
   def hybridize(%__MODULE__{} = req, query, columns, opts)
       when is_list(columns) and length(columns) > 0 do
+    {hybrid_opts, fts_opts} = Keyword.split(opts, [:reranker, :norm, :vector_limit, :fts_limit])
+
     %__MODULE__{
       req
-      | base: req.base |> QueryRequest.fts(query, columns, opts)
+      | base: req.base |> QueryRequest.fts(query, columns, fts_opts),
+        reranker: Keyword.get(hybrid_opts, :reranker, req.reranker),
+        norm: Keyword.get(hybrid_opts, :norm, req.norm),
+        vector_limit: Keyword.get(hybrid_opts, :vector_limit, req.vector_limit),
+        fts_limit: Keyword.get(hybrid_opts, :fts_limit, req.fts_limit)
     }
   end
+
+  defp cast_vector([head | _] = vectors, type) when is_list(head),
+    do: vectors |> Enum.map(&cast_vector(&1, type))
+
+  defp cast_vector(vector, :uint8), do: vector
+  defp cast_vector(vector, _float_type), do: vector |> Enum.map(&(&1 / 1))
 end
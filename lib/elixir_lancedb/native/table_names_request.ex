@@ -0,0 +1,17 @@
+defmodule ElixirLanceDB.Native.TableNamesRequest do
+  defstruct start_after: nil, limit: nil
+
+  @type t() :: %__MODULE__{
+          # names are listed in lexicographic order, so the last name of one
+          # page is the start_after of the next
+          start_after: String.t() | nil,
+          limit: pos_integer() | nil
+        }
+
+  def new(opts \\ []) when is_list(opts) do
+    %__MODULE__{
+      start_after: Keyword.get(opts, :start_after, nil),
+      limit: Keyword.get(opts, :limit, nil)
+    }
+  end
+end
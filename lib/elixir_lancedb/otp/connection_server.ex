@@ -3,6 +3,7 @@ defmodule ElixirLanceDB.Otp.ConnectionServer do
   alias ElixirLanceDB.Native.Table.OptimizeAction.All
   alias ElixirLanceDB.Native, as: LanceDB
   alias ElixirLanceDB.Native.Schema
+  import ElixirLanceDB.Native.Table.Filter, only: [is_filter: 1]
 
   defmodule State do
     defstruct [:conn, tables: %{}]
@@ -106,7 +107,7 @@ defmodule ElixirLanceDB.Otp.ConnectionServer do
     end
   end
 
-  def handle_call({:delete, table_name, filter}, _, %State{} = state) when is_binary(filter) do
+  def handle_call({:delete, table_name, filter}, _, %State{} = state) when is_filter(filter) do
     state |> simple_table_op(table_name, fn table -> table |> LanceDB.delete(filter) end)
   end
 
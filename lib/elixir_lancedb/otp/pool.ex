@@ -2,6 +2,7 @@ defmodule ElieixrLanceDB.Otp.Pool do
   alias ElixirLanceDB.Native.Schema
   alias ElixirLanceDB.Native.Table.{VectorQueryRequest, UpdateConfig, QueryRequest}
   alias ElixirLanceDB.Native, as: LanceDB
+  import ElixirLanceDB.Native.Table.Filter, only: [is_filter: 1]
 
   def child_specs(opts \\ []) do
     if Keyword.get(opts, :conn_uri) |> is_nil do
@@ -114,7 +115,7 @@ defmodule ElieixrLanceDB.Otp.Pool do
     end)
   end
 
-  def delete(table_name, filter) when is_binary(filter) do
+  def delete(table_name, filter) when is_filter(filter) do
     transact_pool(:lancedb_write, fn pid ->
       GenServer.call(pid, {:delete, table_name, filter})
     end)
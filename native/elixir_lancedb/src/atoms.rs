@@ -9,6 +9,9 @@ rustler::atoms! {
     db_mutex_lock_poisoned,
     table_connection_closed,
     table_mutex_lock_poisoned,
+    fts_query_parse_error,
+    message,
+    position,
 
     // Lance Errors
     lance_invalid_table_name,
@@ -66,6 +69,7 @@ rustler::atoms! {
     sql,
     limit,
     columns,
+    returning,
     query_vector,
     full_text_search,
 
@@ -96,18 +100,49 @@ rustler::atoms! {
     // full text search
     query,
     wand_factor,
+    query_type,
+    match_ = "match",
+    phrase,
+    multi_match,
+    boolean,
+    boost,
+    positive,
+    negative,
+    negative_boost,
+
+    // hybrid search
+    reranker_type,
+    rrf,
+    linear_combination,
+    callback,
+    elixir_lancedb_rerank,
 
     // Index Config
     config,
     field,
 
+    // filter bindings
+    year,
+    month,
+    day,
+    hour,
+    minute,
+    second,
+    microsecond,
+    time_zone,
+
     // table merge insert config
     on,
     when_matched_update_all,
     when_matched_update_all_filt,
+    when_matched_update_columns,
+    when_matched_update_columns_filt,
     when_not_matched_insert_all,
     when_not_matched_by_source_delete,
     when_not_matched_by_source_delete_filt,
+    when_not_matched_by_source_update,
+    when_not_matched_by_source_update_filt,
+    duplicate_keys,
 
     // Index Type
     index_type,
@@ -139,6 +174,10 @@ rustler::atoms! {
 
     // adding columns
     transform_type,
+    expressions,
+    field_type,
+    value,
+    elixir_lancedb_add_columns,
 
     // datatypes
     // s,
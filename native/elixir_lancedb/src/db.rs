@@ -5,10 +5,12 @@ use crate::{
     table::TableResource,
 };
 use arrow_array::{RecordBatch, RecordBatchIterator};
-use lancedb::{Connection, Table};
-use rustler::{resource_impl, Resource, ResourceArc, Term};
+use futures::{stream, StreamExt, TryStreamExt};
+use lancedb::{connection::TableNamesBuilder, database::CreateTableMode, Connection, Table};
+use rustler::{resource_impl, NifMap, NifStruct, NifUnitEnum, Resource, ResourceArc, Term};
 
 use std::{
+    collections::HashMap,
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -33,17 +35,92 @@ fn connect(uri: String) -> Result<ResourceArc<DbConnResource>> {
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-fn table_names(conn: ResourceArc<DbConnResource>) -> Result<Vec<String>> {
+fn table_names(
+    conn: ResourceArc<DbConnResource>,
+    request: TableNamesRequest,
+) -> Result<Vec<String>> {
     let conn = db_conn(conn)?;
 
     let result = get_runtime().block_on(async {
-        let names = conn.table_names().execute().await?;
+        let names = request.builder(&conn).execute().await?;
         Ok::<Vec<String>, Error>(names)
     })?;
 
     Ok(result)
 }
 
+#[rustler::nif(schedule = "DirtyCpu")]
+fn list_tables(
+    conn: ResourceArc<DbConnResource>,
+    request: TableNamesRequest,
+) -> Result<Vec<TableSummary>> {
+    let conn = db_conn(conn)?;
+
+    let result = get_runtime().block_on(async {
+        let names = request.builder(&conn).execute().await?;
+        let summaries = stream::iter(names)
+            .map(|name| TableSummary::load(&conn, name))
+            .buffered(SUMMARY_CONCURRENCY)
+            .try_collect::<Vec<TableSummary>>()
+            .await?;
+        Ok::<Vec<TableSummary>, Error>(summaries)
+    })?;
+
+    Ok(result)
+}
+
+#[derive(NifStruct, Debug)]
+#[module = "ElixirLanceDB.Native.TableNamesRequest"]
+pub struct TableNamesRequest {
+    pub start_after: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl TableNamesRequest {
+    fn builder(&self, conn: &Connection) -> TableNamesBuilder {
+        let mut builder = conn.table_names();
+        if let Some(start_after) = &self.start_after {
+            builder = builder.start_after(start_after);
+        }
+        if let Some(limit) = self.limit {
+            builder = builder.limit(limit);
+        }
+        builder
+    }
+}
+
+// tables are opened a few at a time so large listings don't open every table at once
+const SUMMARY_CONCURRENCY: usize = 8;
+
+#[derive(NifMap, Debug)]
+pub struct TableSummary {
+    pub name: String,
+    pub row_count: usize,
+    pub version: u64,
+    pub fields: Vec<String>,
+    pub indices: Vec<String>,
+}
+
+impl TableSummary {
+    async fn load(conn: &Connection, name: String) -> Result<TableSummary> {
+        let table = conn.open_table(&name).execute().await?;
+        let schema = table.schema().await?;
+
+        Ok(TableSummary {
+            row_count: table.count_rows(None).await?,
+            version: table.version().await?,
+            fields: schema.fields().iter().map(|f| f.name().clone()).collect(),
+            indices: table
+                .list_indices()
+                .await?
+                .into_iter()
+                .map(|index| index.name)
+                .collect(),
+            name,
+        })
+    }
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 fn drop_all_tables(conn: ResourceArc<DbConnResource>) -> Result<()> {
     let conn = db_conn(conn)?;
@@ -68,17 +145,52 @@ fn drop_table(conn: ResourceArc<DbConnResource>, table_name: String) -> Result<(
     Ok(())
 }
 
+// Backed by the store's own rename; local directories report lance_not_supported
+#[rustler::nif(schedule = "DirtyCpu")]
+fn rename_table(
+    conn: ResourceArc<DbConnResource>,
+    old_name: String,
+    new_name: String,
+) -> Result<()> {
+    let conn = db_conn(conn)?;
+
+    get_runtime().block_on(async {
+        conn.rename_table(&old_name, &new_name).await?;
+        Ok::<(), Error>(())
+    })?;
+
+    Ok(())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn table_exists(conn: ResourceArc<DbConnResource>, table_name: String) -> Result<bool> {
+    let conn = db_conn(conn)?;
+
+    let result = get_runtime().block_on(async {
+        match conn.open_table(table_name).execute().await {
+            Ok(_) => Ok(true),
+            Err(lancedb::Error::TableNotFound { .. }) => Ok(false),
+            Err(e) => Err(Error::from(e)),
+        }
+    })?;
+
+    Ok(result)
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 fn create_empty_table(
     conn: ResourceArc<DbConnResource>,
     table_name: String,
     schema: schema::Schema,
+    config: CreateConfig,
 ) -> Result<ResourceArc<TableResource>> {
     let conn = db_conn(conn)?;
 
     let table = get_runtime().block_on(async {
         let table = conn
             .create_empty_table(table_name, Arc::new(schema.into()))
+            .storage_options(config.storage_options.clone())
+            .mode(config.into())
             .execute()
             .await?;
 
@@ -96,6 +208,7 @@ fn create_table_with_data(
     table_name: String,
     erl_data: Term,
     erl_schema: schema::Schema,
+    config: CreateConfig,
 ) -> Result<ResourceArc<TableResource>> {
     let arrow_schema: arrow_schema::Schema = erl_schema.into();
     let arc_schema = Arc::new(arrow_schema.clone());
@@ -110,6 +223,8 @@ fn create_table_with_data(
     let table = get_runtime().block_on(async {
         let table = conn
             .create_table(table_name, Box::new(batch))
+            .storage_options(config.storage_options.clone())
+            .mode(config.into())
             .execute()
             .await?;
         Ok::<Table, Error>(table)
@@ -120,6 +235,42 @@ fn create_table_with_data(
     Ok(ResourceArc::new(table_arc))
 }
 
+#[derive(NifStruct, Debug)]
+#[module = "ElixirLanceDB.Native.Table.CreateConfig"]
+pub struct CreateConfig {
+    pub mode: CreateMode,
+    pub storage_options: HashMap<String, String>,
+}
+
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum CreateMode {
+    Create,
+    Overwrite,
+    // opens the existing table instead, failing if its schema differs
+    ExistOk,
+}
+
+impl From<CreateConfig> for CreateTableMode {
+    fn from(value: CreateConfig) -> Self {
+        match value.mode {
+            CreateMode::Create => CreateTableMode::Create,
+            CreateMode::Overwrite => CreateTableMode::Overwrite,
+            CreateMode::ExistOk => {
+                let storage_options = value.storage_options;
+                CreateTableMode::exist_ok(move |mut request| {
+                    request
+                        .lance_read_params
+                        .get_or_insert_with(Default::default)
+                        .store_options
+                        .get_or_insert_with(Default::default)
+                        .storage_options = Some(storage_options);
+                    request
+                })
+            }
+        }
+    }
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 fn open_table(
     conn: ResourceArc<DbConnResource>,
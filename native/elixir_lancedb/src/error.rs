@@ -11,6 +11,7 @@ pub enum Error {
     DbMutexLockPoisoned { message: String },
     TableConnectionClosed { message: String },
     TableMutexLockPoisoned { message: String },
+    FtsQueryParse { message: String, position: usize },
     // Rustler Errors
     RustlerBadArg,
     RustlerAtom { message: String },
@@ -117,6 +118,12 @@ impl From<LanceError> for Error {
     }
 }
 
+impl From<lance::Error> for Error {
+    fn from(error: lance::Error) -> Self {
+        LanceError::from(error).into()
+    }
+}
+
 impl From<ArrowError> for Error {
     fn from(value: ArrowError) -> Self {
         match value {
@@ -180,6 +187,17 @@ impl Encoder for Error {
             Error::TableMutexLockPoisoned { message } => {
                 (atoms::table_mutex_lock_poisoned(), message.to_string())
             }
+            // parse errors carry a position, so they don't fit the (atom, message) shape
+            Error::FtsQueryParse { message, position } => {
+                let mut details = Term::map_new(env);
+                details = details
+                    .map_put(atoms::message(), message.encode(env))
+                    .unwrap_or(details);
+                details = details
+                    .map_put(atoms::position(), position.encode(env))
+                    .unwrap_or(details);
+                return (atoms::fts_query_parse_error(), details).encode(env);
+            }
 
             // Lance
             Error::LanceInvalidTableName { name, reason } => (
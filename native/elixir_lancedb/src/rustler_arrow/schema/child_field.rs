@@ -3,6 +3,8 @@ use std::sync::Arc;
 use arrow_schema::{DataType as ArrowDataType, Field as ArrowField};
 use rustler::{NifStruct, NifUnitEnum};
 
+use crate::error::{Error, Result};
+
 #[derive(NifStruct, Clone, Debug)]
 #[module = "ElixirLanceDB.Native.Schema.Field"]
 
@@ -20,19 +22,25 @@ pub struct ChildField {
 pub enum ChildFieldType {
     Boolean,
     Utf8,
+    Float16,
     Float32,
+    Float64,
     Int32,
     Int64,
+    // packed bits, for binary vectors searched with hamming distance
+    Uint8,
 }
 
-impl From<&Arc<ArrowField>> for ChildField {
-    fn from(value: &Arc<ArrowField>) -> Self {
-        let field_type: ChildFieldType = value.data_type().into();
-        ChildField {
+impl TryFrom<&Arc<ArrowField>> for ChildField {
+    type Error = Error;
+
+    fn try_from(value: &Arc<ArrowField>) -> Result<Self> {
+        let field_type: ChildFieldType = value.data_type().try_into()?;
+        Ok(ChildField {
             name: value.name().to_string(),
             field_type: field_type,
             nullable: value.is_nullable(),
-        }
+        })
     }
 }
 
@@ -43,28 +51,47 @@ impl Into<ArrowField> for ChildField {
                 ArrowField::new(self.name, ArrowDataType::Boolean, self.nullable)
             }
             ChildFieldType::Utf8 => ArrowField::new(self.name, ArrowDataType::Utf8, self.nullable),
+            ChildFieldType::Float16 => {
+                ArrowField::new(self.name, ArrowDataType::Float16, self.nullable)
+            }
             ChildFieldType::Float32 => {
                 ArrowField::new(self.name, ArrowDataType::Float32, self.nullable)
             }
+            ChildFieldType::Float64 => {
+                ArrowField::new(self.name, ArrowDataType::Float64, self.nullable)
+            }
             ChildFieldType::Int32 => {
                 ArrowField::new(self.name, ArrowDataType::Int32, self.nullable)
             },
            ChildFieldType::Int64 => {
                 ArrowField::new(self.name, ArrowDataType::Int64, self.nullable)
-            } 
+            }
+            ChildFieldType::Uint8 => {
+                ArrowField::new(self.name, ArrowDataType::UInt8, self.nullable)
+            }
         }
     }
 }
 
-impl From<&ArrowDataType> for ChildFieldType {
-    fn from(value: &ArrowDataType) -> Self {
-        match value {
+impl TryFrom<&ArrowDataType> for ChildFieldType {
+    type Error = Error;
+
+    fn try_from(value: &ArrowDataType) -> Result<Self> {
+        let field_type = match value {
             ArrowDataType::Boolean => ChildFieldType::Boolean,
             ArrowDataType::Utf8 => ChildFieldType::Utf8,
+            ArrowDataType::Float16 => ChildFieldType::Float16,
             ArrowDataType::Float32 => ChildFieldType::Float32,
+            ArrowDataType::Float64 => ChildFieldType::Float64,
             ArrowDataType::Int32 => ChildFieldType::Int32,
             ArrowDataType::Int64 => ChildFieldType::Int64,
-            _ => todo!("Data type not implemented for {:?}", value),
-        }
+            ArrowDataType::UInt8 => ChildFieldType::Uint8,
+            _ => {
+                return Err(Error::InvalidInput {
+                    message: format!("Data type not implemented for list items: {}", value),
+                })
+            }
+        };
+        Ok(field_type)
     }
 }
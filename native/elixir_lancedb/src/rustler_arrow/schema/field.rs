@@ -3,7 +3,9 @@ use std::sync::Arc;
 use arrow_schema::{DataType as ArrowDataType, Field as ArrowField};
 use rustler::NifStruct;
 
-use super::field_type::FieldType;
+use crate::error::{Error, Result};
+
+use super::field_type::{multi_vector_item, FieldType};
 
 #[derive(NifStruct, Clone, Debug)]
 #[module = "ElixirLanceDB.Native.Schema.Field"]
@@ -13,14 +15,16 @@ pub struct Field {
     pub nullable: bool,
 }
 
-impl From<&Arc<ArrowField>> for Field {
-    fn from(value: &Arc<ArrowField>) -> Self {
-        let field_type: FieldType = value.data_type().into();
-        Field {
+impl TryFrom<&Arc<ArrowField>> for Field {
+    type Error = Error;
+
+    fn try_from(value: &Arc<ArrowField>) -> Result<Self> {
+        let field_type: FieldType = value.data_type().try_into()?;
+        Ok(Field {
             name: value.name().to_string(),
             field_type: field_type,
             nullable: value.is_nullable(),
-        }
+        })
     }
 }
 
@@ -44,6 +48,12 @@ impl Into<ArrowField> for Field {
                 ArrowDataType::FixedSizeList(Arc::new(child.into()), dimension),
                 self.nullable,
             ),
+            FieldType::MultiVector(child, dimension) => ArrowField::new(
+                self.name,
+                ArrowDataType::List(Arc::new(multi_vector_item(child, dimension))),
+                self.nullable,
+            ),
+            field_type => ArrowField::new(self.name, field_type.into(), self.nullable),
         }
     }
 }
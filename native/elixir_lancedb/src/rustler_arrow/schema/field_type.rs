@@ -1,39 +1,96 @@
 use std::sync::Arc;
 
-use arrow_schema::DataType as ArrowDataType;
-use rustler::NifTaggedEnum;
+use arrow_schema::{DataType as ArrowDataType, Field as ArrowField, TimeUnit as ArrowTimeUnit};
+use rustler::{NifTaggedEnum, NifUnitEnum};
 
-use super::child_field::ChildField;
+use crate::error::{Error, Result};
+
+use super::{child_field::ChildField, field::Field};
 
 #[derive(NifTaggedEnum, Debug, Clone)]
 pub enum FieldType {
     Boolean,
     Utf8,
+    LargeUtf8,
+    Binary,
+    LargeBinary,
+    Float16,
     Float32,
+    Float64,
+    Int8,
+    Int16,
     Int32,
     Int64,
+    Uint8,
+    Uint16,
+    Uint32,
+    Uint64,
+    Decimal128(u8, i8),
     Date32,
     Date64,
+    // without a time zone, like Lance's SQL timestamps
+    Timestamp(TimeUnit),
     List(ChildField),
     FixedSizeList(ChildField, i32),
+    // List<FixedSizeList<child>>, e.g. one embedding per token for late interaction
+    MultiVector(ChildField, i32),
+    Struct(Vec<Field>),
 }
 
-impl From<&ArrowDataType> for FieldType {
-    fn from(value: &ArrowDataType) -> Self {
-        match value {
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum TimeUnit {
+    Second,
+    Millisecond,
+    Microsecond,
+    Nanosecond,
+}
+
+impl TryFrom<&ArrowDataType> for FieldType {
+    type Error = Error;
+
+    fn try_from(value: &ArrowDataType) -> Result<Self> {
+        let field_type = match value {
             ArrowDataType::Boolean => FieldType::Boolean,
             ArrowDataType::Utf8 => FieldType::Utf8,
+            ArrowDataType::LargeUtf8 => FieldType::LargeUtf8,
+            ArrowDataType::Binary => FieldType::Binary,
+            ArrowDataType::LargeBinary => FieldType::LargeBinary,
+            ArrowDataType::Float16 => FieldType::Float16,
             ArrowDataType::Float32 => FieldType::Float32,
+            ArrowDataType::Float64 => FieldType::Float64,
+            ArrowDataType::Int8 => FieldType::Int8,
+            ArrowDataType::Int16 => FieldType::Int16,
             ArrowDataType::Int32 => FieldType::Int32,
             ArrowDataType::Int64 => FieldType::Int64,
+            ArrowDataType::UInt8 => FieldType::Uint8,
+            ArrowDataType::UInt16 => FieldType::Uint16,
+            ArrowDataType::UInt32 => FieldType::Uint32,
+            ArrowDataType::UInt64 => FieldType::Uint64,
+            ArrowDataType::Decimal128(precision, scale) => {
+                FieldType::Decimal128(*precision, *scale)
+            }
             ArrowDataType::Date64 => FieldType::Date64,
             ArrowDataType::Date32 => FieldType::Date32,
-            ArrowDataType::List(child_field) => FieldType::List(child_field.into()),
+            ArrowDataType::Timestamp(unit, None) => FieldType::Timestamp(unit.into()),
+            ArrowDataType::Struct(fields) => {
+                FieldType::Struct(fields.iter().map(Field::try_from).collect::<Result<_>>()?)
+            }
+            ArrowDataType::List(child_field) => match child_field.data_type() {
+                ArrowDataType::FixedSizeList(vector_field, dim) => {
+                    FieldType::MultiVector(vector_field.try_into()?, *dim)
+                }
+                _ => FieldType::List(child_field.try_into()?),
+            },
             ArrowDataType::FixedSizeList(child_field, dim) => {
-                FieldType::FixedSizeList(child_field.into(), *dim)
+                FieldType::FixedSizeList(child_field.try_into()?, *dim)
             }
-            _ => todo!("Data type not implemented for {:?}", value),
-        }
+            _ => {
+                return Err(Error::InvalidInput {
+                    message: format!("Data type not implemented: {}", value),
+                })
+            }
+        };
+        Ok(field_type)
     }
 }
 
@@ -42,15 +99,64 @@ impl Into<ArrowDataType> for FieldType {
         match self {
             FieldType::Boolean => ArrowDataType::Boolean,
             FieldType::Utf8 => ArrowDataType::Utf8,
+            FieldType::LargeUtf8 => ArrowDataType::LargeUtf8,
+            FieldType::Binary => ArrowDataType::Binary,
+            FieldType::LargeBinary => ArrowDataType::LargeBinary,
+            FieldType::Float16 => ArrowDataType::Float16,
             FieldType::Float32 => ArrowDataType::Float32,
+            FieldType::Float64 => ArrowDataType::Float64,
+            FieldType::Int8 => ArrowDataType::Int8,
+            FieldType::Int16 => ArrowDataType::Int16,
             FieldType::Int32 => ArrowDataType::Int32,
             FieldType::Int64 => ArrowDataType::Int64,
+            FieldType::Uint8 => ArrowDataType::UInt8,
+            FieldType::Uint16 => ArrowDataType::UInt16,
+            FieldType::Uint32 => ArrowDataType::UInt32,
+            FieldType::Uint64 => ArrowDataType::UInt64,
+            FieldType::Decimal128(precision, scale) => ArrowDataType::Decimal128(precision, scale),
             FieldType::Date64 => ArrowDataType::Date64,
             FieldType::Date32 => ArrowDataType::Date32,
+            FieldType::Timestamp(unit) => ArrowDataType::Timestamp(unit.into(), None),
             FieldType::List(child_type) => ArrowDataType::List(Arc::new(child_type.into())),
             FieldType::FixedSizeList(child_type, dim) => {
                 ArrowDataType::FixedSizeList(Arc::new(child_type.into()), dim)
             }
+            FieldType::MultiVector(child_type, dim) => {
+                ArrowDataType::List(Arc::new(multi_vector_item(child_type, dim)))
+            }
+            FieldType::Struct(fields) => {
+                ArrowDataType::Struct(fields.into_iter().map(Into::<ArrowField>::into).collect())
+            }
+        }
+    }
+}
+
+impl From<&ArrowTimeUnit> for TimeUnit {
+    fn from(value: &ArrowTimeUnit) -> Self {
+        match value {
+            ArrowTimeUnit::Second => TimeUnit::Second,
+            ArrowTimeUnit::Millisecond => TimeUnit::Millisecond,
+            ArrowTimeUnit::Microsecond => TimeUnit::Microsecond,
+            ArrowTimeUnit::Nanosecond => TimeUnit::Nanosecond,
+        }
+    }
+}
+
+impl From<TimeUnit> for ArrowTimeUnit {
+    fn from(value: TimeUnit) -> Self {
+        match value {
+            TimeUnit::Second => ArrowTimeUnit::Second,
+            TimeUnit::Millisecond => ArrowTimeUnit::Millisecond,
+            TimeUnit::Microsecond => ArrowTimeUnit::Microsecond,
+            TimeUnit::Nanosecond => ArrowTimeUnit::Nanosecond,
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn multi_vector_item(child: ChildField, dim: i32) -> ArrowField {
+    ArrowField::new(
+        "item",
+        ArrowDataType::FixedSizeList(Arc::new(child.into()), dim),
+        true,
+    )
+}
@@ -2,6 +2,7 @@ mod child_field;
 mod field;
 pub mod field_type;
 
+use crate::error::{Error, Result};
 use arrow_schema::{Field as ArrowField, Schema as ArrowSchema};
 use field::Field;
 use rustler::NifStruct;
@@ -21,11 +22,13 @@ impl Into<ArrowSchema> for Schema {
     }
 }
 
-impl From<Arc<ArrowSchema>> for Schema {
-    fn from(value: Arc<ArrowSchema>) -> Self {
-        Schema {
-            fields: value.fields.iter().map(|f| f.into()).collect(),
+impl TryFrom<Arc<ArrowSchema>> for Schema {
+    type Error = Error;
+
+    fn try_from(value: Arc<ArrowSchema>) -> Result<Self> {
+        Ok(Schema {
+            fields: value.fields.iter().map(Field::try_from).collect::<Result<_>>()?,
             metadata: value.metadata.clone(),
-        }
+        })
     }
 }
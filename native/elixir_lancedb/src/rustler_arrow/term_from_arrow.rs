@@ -2,8 +2,8 @@ use crate::error::Error;
 use crate::error::Result;
 use arrow_array::Array;
 use arrow_array::RecordBatch;
-use arrow_schema::DataType;
-use rustler::{Encoder, Env, Term};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use rustler::{Encoder, Env, OwnedBinary, Term};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -11,12 +11,19 @@ use std::sync::Arc;
 pub enum ReturnableTerm {
     Boolean(bool),
     Utf8(String),
+    Binary(Vec<u8>),
     Int32(i32),
     Int64(i64),
+    UInt64(u64),
     Float32(f32),
+    Float64(f64),
     ListFloat32(Vec<f32>),
+    ListFloat64(Vec<f64>),
     ListInt32(Vec<i32>),
+    ListUInt8(Vec<u8>),
     ListUtf8(Vec<String>),
+    List(Vec<ReturnableTerm>),
+    Map(HashMap<String, ReturnableTerm>),
 }
 
 impl Encoder for ReturnableTerm {
@@ -24,150 +31,298 @@ impl Encoder for ReturnableTerm {
         match &self {
             ReturnableTerm::Boolean(val) => val.encode(env),
             ReturnableTerm::Utf8(val) => val.encode(env),
+            ReturnableTerm::Binary(val) => match OwnedBinary::new(val.len()) {
+                Some(mut binary) => {
+                    binary.as_mut_slice().copy_from_slice(val);
+                    binary.release(env).encode(env)
+                }
+                None => rustler::types::atom::error().encode(env),
+            },
             ReturnableTerm::Int32(val) => val.encode(env),
             ReturnableTerm::Int64(val) => val.encode(env),
+            ReturnableTerm::UInt64(val) => val.encode(env),
             ReturnableTerm::Float32(val) => val.encode(env),
+            ReturnableTerm::Float64(val) => val.encode(env),
             ReturnableTerm::ListFloat32(val) => val.encode(env),
+            ReturnableTerm::ListFloat64(val) => val.encode(env),
             ReturnableTerm::ListInt32(val) => val.encode(env),
+            ReturnableTerm::ListUInt8(val) => val.encode(env),
             ReturnableTerm::ListUtf8(val) => val.encode(env),
+            ReturnableTerm::List(val) => val.encode(env),
+            ReturnableTerm::Map(val) => val.encode(env),
         }
     }
 }
 
 pub fn from_arrow(results: Vec<RecordBatch>) -> Result<Vec<HashMap<String, ReturnableTerm>>> {
-    let empty_recs: Vec<HashMap<String, ReturnableTerm>> = vec![];
-    let records: Vec<HashMap<String, ReturnableTerm>> =
-        results.into_iter().fold(empty_recs, |mut recs, batch| {
-            let num_rows = batch.num_rows();
-            let num_columns = batch.num_columns();
-            let batch_schema = batch.schema();
-
-            for row_idx in 0..num_rows {
-                let mut record: HashMap<String, ReturnableTerm> = HashMap::new();
-
-                for col_idx in 0..num_columns {
-                    let field = &batch_schema.fields[col_idx];
-                    let column = batch.column(col_idx);
-                    let value = match field.data_type() {
-                        DataType::Boolean => {
-                            if let Some(bool_array) =
-                                column.as_any().downcast_ref::<arrow_array::BooleanArray>()
-                            {
-                                let val = bool_array.value(row_idx);
-                                ReturnableTerm::Boolean(val)
-                            } else {
-                                ReturnableTerm::Boolean(false)
-                            }
-                        }
-                        DataType::Utf8 => {
-                            if let Some(string_array) =
-                                column.as_any().downcast_ref::<arrow_array::StringArray>()
-                            {
-                                let val = string_array.value(row_idx);
-                                ReturnableTerm::Utf8(val.to_string())
-                            } else {
-                                ReturnableTerm::Utf8("".to_string())
-                            }
-                        }
-                        DataType::Int32 => {
-                            if let Some(int_array) =
-                                column.as_any().downcast_ref::<arrow_array::Int32Array>()
-                            {
-                                let value = int_array.value(row_idx);
-                                ReturnableTerm::Int32(value)
-                            } else {
-                                ReturnableTerm::Int32(0)
-                            }
-                        }
-                        DataType::Float32 => {
-                            if let Some(float_array) =
-                                column.as_any().downcast_ref::<arrow_array::Float32Array>()
-                            {
-                                let value = float_array.value(row_idx);
-                                ReturnableTerm::Float32(value)
-                            } else {
-                                ReturnableTerm::Float32(0.0)
-                            }
-                        }
-                        DataType::Date32 => {
-                            if let Some(date_array) =
-                                column.as_any().downcast_ref::<arrow_array::Date32Array>()
-                            {
-                                let value = date_array.value(row_idx);
-                                ReturnableTerm::Int32(value)
-                            } else {
-                                ReturnableTerm::Int32(0)
-                            }
-                        }
-                        DataType::Date64 => {
-                            if let Some(date_array) =
-                                column.as_any().downcast_ref::<arrow_array::Date64Array>()
-                            {
-                                let value = date_array.value(row_idx);
-                                ReturnableTerm::Int64(value)
-                            } else {
-                                ReturnableTerm::Int64(0)
-                            }
-                        }
-                        DataType::List(field) => {
-                            if let Some(list_array) =
-                                column.as_any().downcast_ref::<arrow_array::ListArray>()
-                            {
-                                match field.data_type() {
-                                    DataType::Utf8 => array_to_values(&list_array.value(row_idx))
-                                        .unwrap_or(ReturnableTerm::ListUtf8(vec![])),
-                                    DataType::Float32 => {
-                                        array_to_values(&list_array.value(row_idx))
-                                            .unwrap_or(ReturnableTerm::ListFloat32(vec![]))
-                                    }
-                                    DataType::Int32 => array_to_values(&list_array.value(row_idx))
-                                        .unwrap_or(ReturnableTerm::ListInt32(vec![])),
-
-                                    _ => todo!(),
-                                }
-                            } else {
-                                match field.data_type() {
-                                    DataType::Utf8 => ReturnableTerm::ListUtf8(vec![]),
-                                    DataType::Int32 => ReturnableTerm::ListInt32(vec![]),
-                                    DataType::Float32 => ReturnableTerm::ListFloat32(vec![]),
-                                    _ => todo!(),
-                                }
-                            }
-                        }
-                        DataType::FixedSizeList(field, _d) => {
-                            if let Some(list_array) = column
+    let mut records: Vec<HashMap<String, ReturnableTerm>> = vec![];
+    for batch in results {
+        let num_rows = batch.num_rows();
+        let num_columns = batch.num_columns();
+        let batch_schema = batch.schema();
+
+        for row_idx in 0..num_rows {
+            let mut record: HashMap<String, ReturnableTerm> = HashMap::new();
+
+            for col_idx in 0..num_columns {
+                let field = &batch_schema.fields[col_idx];
+                let column = batch.column(col_idx);
+                let value = match field.data_type() {
+                    DataType::Boolean => {
+                        if let Some(bool_array) =
+                            column.as_any().downcast_ref::<arrow_array::BooleanArray>()
+                        {
+                            let val = bool_array.value(row_idx);
+                            ReturnableTerm::Boolean(val)
+                        } else {
+                            ReturnableTerm::Boolean(false)
+                        }
+                    }
+                    DataType::Utf8 => {
+                        if let Some(string_array) =
+                            column.as_any().downcast_ref::<arrow_array::StringArray>()
+                        {
+                            let val = string_array.value(row_idx);
+                            ReturnableTerm::Utf8(val.to_string())
+                        } else {
+                            ReturnableTerm::Utf8("".to_string())
+                        }
+                    }
+                    DataType::LargeUtf8 => {
+                        if let Some(string_array) = column
+                            .as_any()
+                            .downcast_ref::<arrow_array::LargeStringArray>()
+                        {
+                            let val = string_array.value(row_idx);
+                            ReturnableTerm::Utf8(val.to_string())
+                        } else {
+                            ReturnableTerm::Utf8("".to_string())
+                        }
+                    }
+                    DataType::Binary => {
+                        if let Some(binary_array) =
+                            column.as_any().downcast_ref::<arrow_array::BinaryArray>()
+                        {
+                            let val = binary_array.value(row_idx);
+                            ReturnableTerm::Binary(val.to_vec())
+                        } else {
+                            ReturnableTerm::Binary(vec![])
+                        }
+                    }
+                    DataType::LargeBinary => {
+                        if let Some(binary_array) = column
+                            .as_any()
+                            .downcast_ref::<arrow_array::LargeBinaryArray>()
+                        {
+                            let val = binary_array.value(row_idx);
+                            ReturnableTerm::Binary(val.to_vec())
+                        } else {
+                            ReturnableTerm::Binary(vec![])
+                        }
+                    }
+                    DataType::Int8 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::Int8Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::Int32(value.into())
+                        } else {
+                            ReturnableTerm::Int32(0)
+                        }
+                    }
+                    DataType::Int16 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::Int16Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::Int32(value.into())
+                        } else {
+                            ReturnableTerm::Int32(0)
+                        }
+                    }
+                    DataType::Int32 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::Int32Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::Int32(value)
+                        } else {
+                            ReturnableTerm::Int32(0)
+                        }
+                    }
+                    DataType::Int64 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::Int64Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::Int64(value)
+                        } else {
+                            ReturnableTerm::Int64(0)
+                        }
+                    }
+                    DataType::UInt8 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::UInt8Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::UInt64(value.into())
+                        } else {
+                            ReturnableTerm::UInt64(0)
+                        }
+                    }
+                    DataType::UInt16 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::UInt16Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::UInt64(value.into())
+                        } else {
+                            ReturnableTerm::UInt64(0)
+                        }
+                    }
+                    DataType::UInt32 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::UInt32Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::UInt64(value.into())
+                        } else {
+                            ReturnableTerm::UInt64(0)
+                        }
+                    }
+                    DataType::UInt64 => {
+                        if let Some(int_array) =
+                            column.as_any().downcast_ref::<arrow_array::UInt64Array>()
+                        {
+                            let value = int_array.value(row_idx);
+                            ReturnableTerm::UInt64(value)
+                        } else {
+                            ReturnableTerm::UInt64(0)
+                        }
+                    }
+                    DataType::Float16 => {
+                        if let Some(float_array) =
+                            column.as_any().downcast_ref::<arrow_array::Float16Array>()
+                        {
+                            let value = float_array.value(row_idx);
+                            ReturnableTerm::Float32(value.to_f32())
+                        } else {
+                            ReturnableTerm::Float32(0.0)
+                        }
+                    }
+                    DataType::Float32 => {
+                        if let Some(float_array) =
+                            column.as_any().downcast_ref::<arrow_array::Float32Array>()
+                        {
+                            let value = float_array.value(row_idx);
+                            ReturnableTerm::Float32(value)
+                        } else {
+                            ReturnableTerm::Float32(0.0)
+                        }
+                    }
+                    DataType::Float64 => {
+                        if let Some(float_array) =
+                            column.as_any().downcast_ref::<arrow_array::Float64Array>()
+                        {
+                            let value = float_array.value(row_idx);
+                            ReturnableTerm::Float64(value)
+                        } else {
+                            ReturnableTerm::Float64(0.0)
+                        }
+                    }
+                    // as a string, since the scale may exceed what a float holds exactly
+                    DataType::Decimal128(_, _) => {
+                        if let Some(decimal_array) = column
+                            .as_any()
+                            .downcast_ref::<arrow_array::Decimal128Array>()
+                        {
+                            ReturnableTerm::Utf8(decimal_array.value_as_string(row_idx))
+                        } else {
+                            ReturnableTerm::Utf8("".to_string())
+                        }
+                    }
+                    // in the column's unit since the epoch, like dates
+                    DataType::Timestamp(unit, _) => {
+                        let value = match unit {
+                            TimeUnit::Second => column
                                 .as_any()
-                                .downcast_ref::<arrow_array::FixedSizeListArray>()
-                            {
-                                match field.data_type() {
-                                    DataType::Utf8 => array_to_values(&list_array.value(row_idx))
-                                        .unwrap_or(ReturnableTerm::ListUtf8(vec![])),
-
-                                    DataType::Float32 => {
-                                        array_to_values(&list_array.value(row_idx))
-                                            .unwrap_or(ReturnableTerm::ListFloat32(vec![]))
-                                    }
-                                    DataType::Int32 => array_to_values(&list_array.value(row_idx))
-                                        .unwrap_or(ReturnableTerm::ListInt32(vec![])),
-                                    _ => todo!(),
-                                }
-                            } else {
-                                match field.data_type() {
-                                    DataType::Utf8 => ReturnableTerm::ListUtf8(vec![]),
-                                    DataType::Int32 => ReturnableTerm::ListInt32(vec![]),
-                                    DataType::Float32 => ReturnableTerm::ListFloat32(vec![]),
-                                    _ => todo!(),
-                                }
-                            }
-                        }
-                        _ => panic!("Unsupported data type: {:?}", field.data_type()),
-                    };
-                    record.insert(field.name().to_string(), value);
-                }
-                recs.push(record);
+                                .downcast_ref::<arrow_array::TimestampSecondArray>()
+                                .map(|array| array.value(row_idx)),
+                            TimeUnit::Millisecond => column
+                                .as_any()
+                                .downcast_ref::<arrow_array::TimestampMillisecondArray>()
+                                .map(|array| array.value(row_idx)),
+                            TimeUnit::Microsecond => column
+                                .as_any()
+                                .downcast_ref::<arrow_array::TimestampMicrosecondArray>()
+                                .map(|array| array.value(row_idx)),
+                            TimeUnit::Nanosecond => column
+                                .as_any()
+                                .downcast_ref::<arrow_array::TimestampNanosecondArray>()
+                                .map(|array| array.value(row_idx)),
+                        };
+                        ReturnableTerm::Int64(value.unwrap_or(0))
+                    }
+                    DataType::Struct(_) => {
+                        if let Some(struct_array) =
+                            column.as_any().downcast_ref::<arrow_array::StructArray>()
+                        {
+                            let row = RecordBatch::from(struct_array.slice(row_idx, 1));
+                            ReturnableTerm::Map(from_arrow(vec![row])?.pop().unwrap_or_default())
+                        } else {
+                            ReturnableTerm::Map(HashMap::new())
+                        }
+                    }
+                    DataType::Date32 => {
+                        if let Some(date_array) =
+                            column.as_any().downcast_ref::<arrow_array::Date32Array>()
+                        {
+                            let value = date_array.value(row_idx);
+                            ReturnableTerm::Int32(value)
+                        } else {
+                            ReturnableTerm::Int32(0)
+                        }
+                    }
+                    DataType::Date64 => {
+                        if let Some(date_array) =
+                            column.as_any().downcast_ref::<arrow_array::Date64Array>()
+                        {
+                            let value = date_array.value(row_idx);
+                            ReturnableTerm::Int64(value)
+                        } else {
+                            ReturnableTerm::Int64(0)
+                        }
+                    }
+                    DataType::List(_) => {
+                        if let Some(list_array) =
+                            column.as_any().downcast_ref::<arrow_array::ListArray>()
+                        {
+                            array_to_values(&list_array.value(row_idx))?
+                        } else {
+                            ReturnableTerm::List(vec![])
+                        }
+                    }
+                    DataType::FixedSizeList(_, _) => {
+                        if let Some(list_array) = column
+                            .as_any()
+                            .downcast_ref::<arrow_array::FixedSizeListArray>()
+                        {
+                            array_to_values(&list_array.value(row_idx))?
+                        } else {
+                            ReturnableTerm::List(vec![])
+                        }
+                    }
+                    _ => {
+                        return Err(Error::InvalidInput {
+                            message: format!("Unsupported data type: {}", field.data_type()),
+                        })
+                    }
+                };
+                record.insert(field.name().to_string(), value);
             }
-            recs
-        });
+            records.push(record);
+        }
+    }
     Ok(records)
 }
 
@@ -202,6 +357,48 @@ fn array_to_values(array: &Arc<dyn Array>) -> Result<ReturnableTerm> {
             Ok(ReturnableTerm::ListFloat32(values))
         }
 
+        DataType::Float16 => {
+            let typed_array = array
+                .as_any()
+                .downcast_ref::<arrow_array::Float16Array>()
+                .ok_or_else(|| "Failed to downcast to Float16Array".to_string())?;
+
+            let values: Vec<f32> = typed_array
+                .iter()
+                .map(|opt_val| opt_val.map(|val| val.to_f32()).unwrap_or(0.0)) // Handle nulls
+                .collect();
+
+            Ok(ReturnableTerm::ListFloat32(values))
+        }
+
+        DataType::Float64 => {
+            let typed_array = array
+                .as_any()
+                .downcast_ref::<arrow_array::Float64Array>()
+                .ok_or_else(|| "Failed to downcast to Float64Array".to_string())?;
+
+            let values: Vec<f64> = typed_array
+                .iter()
+                .map(|opt_val| opt_val.unwrap_or(0.0)) // Handle nulls
+                .collect();
+
+            Ok(ReturnableTerm::ListFloat64(values))
+        }
+
+        DataType::UInt8 => {
+            let typed_array = array
+                .as_any()
+                .downcast_ref::<arrow_array::UInt8Array>()
+                .ok_or_else(|| "Failed to downcast to UInt8Array".to_string())?;
+
+            let values: Vec<u8> = typed_array
+                .iter()
+                .map(|opt_val| opt_val.unwrap_or(0)) // Handle nulls
+                .collect();
+
+            Ok(ReturnableTerm::ListUInt8(values))
+        }
+
         DataType::Utf8 => {
             let typed_array = array
                 .as_any()
@@ -216,9 +413,30 @@ fn array_to_values(array: &Arc<dyn Array>) -> Result<ReturnableTerm> {
             Ok(ReturnableTerm::ListUtf8(values))
         }
 
-        // Add more type handling as needed
-        _ => Err(Error::InvalidInput {
-            message: format!("Unsupported data type: {}", array.data_type()),
-        }),
+        // multivectors: one list per vector
+        DataType::FixedSizeList(_, _) => {
+            let typed_array = array
+                .as_any()
+                .downcast_ref::<arrow_array::FixedSizeListArray>()
+                .ok_or_else(|| "Failed to downcast to FixedSizeListArray".to_string())?;
+
+            let values: Result<Vec<ReturnableTerm>> = (0..typed_array.len())
+                .map(|idx| array_to_values(&typed_array.value(idx)))
+                .collect();
+
+            Ok(ReturnableTerm::List(values?))
+        }
+
+        // any other child, e.g. booleans or int64s, one scalar term per element
+        data_type => {
+            let schema = Schema::new(vec![Field::new("item", data_type.clone(), true)]);
+            let batch = RecordBatch::try_new(Arc::new(schema), vec![array.clone()])?;
+            let values = from_arrow(vec![batch])?
+                .into_iter()
+                .filter_map(|mut row| row.remove("item"))
+                .collect();
+
+            Ok(ReturnableTerm::List(values))
+        }
     }
 }
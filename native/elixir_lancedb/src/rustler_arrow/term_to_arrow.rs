@@ -1,13 +1,22 @@
+use std::sync::Arc;
+
 use crate::error::{Error, Result};
 use arrow_array::{
     builder::{
-        ArrayBuilder, BooleanBuilder, Date32Builder, Date64Builder, FixedSizeListBuilder,
-        Float32Builder, Int32Builder, ListBuilder, StringBuilder,
+        ArrayBuilder, BinaryBuilder, BooleanBuilder, Date32Builder, Date64Builder,
+        Decimal128Builder, FixedSizeListBuilder, Float16Builder, Float32Builder, Float64Builder,
+        Int16Builder, Int32Builder, Int64Builder, Int8Builder, LargeBinaryBuilder,
+        LargeStringBuilder, ListBuilder, NullBuilder, StringBuilder, TimestampMicrosecondBuilder,
+        TimestampMillisecondBuilder, TimestampNanosecondBuilder, TimestampSecondBuilder,
+        UInt16Builder, UInt32Builder, UInt64Builder, UInt8Builder,
     },
-    ArrayRef,
+    types::Decimal128Type,
+    ArrayRef, StructArray,
 };
-use arrow_schema::DataType;
-use rustler::Term;
+use arrow_cast::parse::parse_decimal;
+use arrow_schema::{DataType, FieldRef, Fields, Schema, TimeUnit};
+use half::f16;
+use rustler::{Binary, Encoder, Term};
 
 pub fn to_arrow(term: Term, schema: arrow_schema::Schema) -> Result<Vec<ArrayRef>> {
     if !term.is_list() {
@@ -21,12 +30,35 @@ pub fn to_arrow(term: Term, schema: arrow_schema::Schema) -> Result<Vec<ArrayRef
             .clone()
             .fields()
             .into_iter()
-            .fold(vec![], |mut acc, field| {
+            .try_fold(vec![], |mut acc, field| {
                 let builder: Box<dyn ArrayBuilder> = match field.data_type() {
                     DataType::Boolean => Box::new(BooleanBuilder::new()),
                     DataType::Utf8 => Box::new(StringBuilder::new()),
+                    DataType::LargeUtf8 => Box::new(LargeStringBuilder::new()),
+                    DataType::Binary => Box::new(BinaryBuilder::new()),
+                    DataType::LargeBinary => Box::new(LargeBinaryBuilder::new()),
+                    DataType::Float16 => Box::new(Float16Builder::new()),
                     DataType::Float32 => Box::new(Float32Builder::new()),
+                    DataType::Float64 => Box::new(Float64Builder::new()),
+                    DataType::UInt8 => Box::new(UInt8Builder::new()),
+                    DataType::UInt16 => Box::new(UInt16Builder::new()),
+                    DataType::UInt32 => Box::new(UInt32Builder::new()),
+                    DataType::UInt64 => Box::new(UInt64Builder::new()),
+                    DataType::Int8 => Box::new(Int8Builder::new()),
+                    DataType::Int16 => Box::new(Int16Builder::new()),
                     DataType::Int32 => Box::new(Int32Builder::new()),
+                    DataType::Int64 => Box::new(Int64Builder::new()),
+                    DataType::Decimal128(_, _) => {
+                        Box::new(Decimal128Builder::new().with_data_type(field.data_type().clone()))
+                    }
+                    DataType::Timestamp(unit, _) => match unit {
+                        TimeUnit::Second => Box::new(TimestampSecondBuilder::new()),
+                        TimeUnit::Millisecond => Box::new(TimestampMillisecondBuilder::new()),
+                        TimeUnit::Microsecond => Box::new(TimestampMicrosecondBuilder::new()),
+                        TimeUnit::Nanosecond => Box::new(TimestampNanosecondBuilder::new()),
+                    },
+                    // struct values are gathered per column and converted as rows of their own
+                    DataType::Struct(_) => Box::new(NullBuilder::new()),
                     DataType::Date64 => Box::new(Date64Builder::new()),
                     DataType::Date32 => Box::new(Date32Builder::new()),
                     DataType::List(child) => match child.data_type() {
@@ -42,7 +74,59 @@ pub fn to_arrow(term: Term, schema: arrow_schema::Schema) -> Result<Vec<ArrayRef
                         DataType::Int32 => {
                             Box::new(ListBuilder::<Int32Builder>::new(Int32Builder::new()))
                         }
-                        _ => panic!("Unsupported data type {}", child.data_type()),
+                        DataType::Float16 => {
+                            Box::new(ListBuilder::<Float16Builder>::new(Float16Builder::new()))
+                        }
+                        DataType::Float64 => {
+                            Box::new(ListBuilder::<Float64Builder>::new(Float64Builder::new()))
+                        }
+                        DataType::UInt8 => {
+                            Box::new(ListBuilder::<UInt8Builder>::new(UInt8Builder::new()))
+                        }
+                        DataType::Int64 => {
+                            Box::new(ListBuilder::<Int64Builder>::new(Int64Builder::new()))
+                        }
+                        DataType::FixedSizeList(vector_field, dimension)
+                            if vector_field.data_type() == &DataType::Float16 =>
+                        {
+                            multi_vector_builder(
+                                Float16Builder::new(),
+                                child,
+                                vector_field,
+                                *dimension,
+                            )
+                        }
+                        DataType::FixedSizeList(vector_field, dimension)
+                            if vector_field.data_type() == &DataType::Float32 =>
+                        {
+                            multi_vector_builder(
+                                Float32Builder::new(),
+                                child,
+                                vector_field,
+                                *dimension,
+                            )
+                        }
+                        DataType::FixedSizeList(vector_field, dimension)
+                            if vector_field.data_type() == &DataType::Float64 =>
+                        {
+                            multi_vector_builder(
+                                Float64Builder::new(),
+                                child,
+                                vector_field,
+                                *dimension,
+                            )
+                        }
+                        DataType::FixedSizeList(vector_field, dimension)
+                            if vector_field.data_type() == &DataType::UInt8 =>
+                        {
+                            multi_vector_builder(
+                                UInt8Builder::new(),
+                                child,
+                                vector_field,
+                                *dimension,
+                            )
+                        }
+                        _ => return Err(unsupported(field.data_type())),
                     },
                     DataType::FixedSizeList(child, dimension) => match child.data_type() {
                         DataType::Boolean => Box::new(FixedSizeListBuilder::<BooleanBuilder>::new(
@@ -61,20 +145,135 @@ pub fn to_arrow(term: Term, schema: arrow_schema::Schema) -> Result<Vec<ArrayRef
                             Int32Builder::new(),
                             *dimension,
                         )),
-                        _ => panic!("Unsupported data type {}", child.data_type()),
+                        DataType::Float16 => Box::new(FixedSizeListBuilder::<Float16Builder>::new(
+                            Float16Builder::new(),
+                            *dimension,
+                        )),
+                        DataType::Float64 => Box::new(FixedSizeListBuilder::<Float64Builder>::new(
+                            Float64Builder::new(),
+                            *dimension,
+                        )),
+                        DataType::UInt8 => Box::new(FixedSizeListBuilder::<UInt8Builder>::new(
+                            UInt8Builder::new(),
+                            *dimension,
+                        )),
+                        DataType::Int64 => Box::new(FixedSizeListBuilder::<Int64Builder>::new(
+                            Int64Builder::new(),
+                            *dimension,
+                        )),
+                        _ => return Err(unsupported(field.data_type())),
                     },
-                    _ => panic!("Unsupported data type {}", field.data_type()),
+                    _ => return Err(unsupported(field.data_type())),
                 };
                 acc.push(builder);
-                acc
-            });
+                Ok::<_, Error>(acc)
+            })?;
 
+    let mut struct_rows: Vec<Vec<Term>> = vec![vec![]; schema.fields().len()];
     let builders: Result<Vec<Box<dyn ArrayBuilder>>> =
         term.into_list_iterator()?
             .try_fold(empty_cols, |mut acc, record| {
                 for (idx, field) in schema.fields().into_iter().enumerate() {
                     let val = record.map_get(field.name())?;
                     match field.data_type() {
+                        DataType::Struct(_) => struct_rows[idx].push(val),
+                        DataType::LargeUtf8 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<LargeStringBuilder>()
+                            {
+                                let the_str: String = val.decode()?;
+                                builder.append_value(the_str);
+                            }
+                        }
+                        DataType::Binary => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<BinaryBuilder>()
+                            {
+                                let the_bin: Binary = val.decode()?;
+                                builder.append_value(the_bin.as_slice());
+                            }
+                        }
+                        DataType::LargeBinary => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<LargeBinaryBuilder>()
+                            {
+                                let the_bin: Binary = val.decode()?;
+                                builder.append_value(the_bin.as_slice());
+                            }
+                        }
+                        DataType::Int8 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<Int8Builder>()
+                            {
+                                let the_int: i8 = val.decode()?;
+                                builder.append_value(the_int);
+                            }
+                        }
+                        DataType::Int16 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<Int16Builder>()
+                            {
+                                let the_int: i16 = val.decode()?;
+                                builder.append_value(the_int);
+                            }
+                        }
+                        DataType::Int64 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<Int64Builder>()
+                            {
+                                let the_int: i64 = val.decode()?;
+                                builder.append_value(the_int);
+                            }
+                        }
+                        DataType::UInt16 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<UInt16Builder>()
+                            {
+                                let the_int: u16 = val.decode()?;
+                                builder.append_value(the_int);
+                            }
+                        }
+                        DataType::UInt32 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<UInt32Builder>()
+                            {
+                                let the_int: u32 = val.decode()?;
+                                builder.append_value(the_int);
+                            }
+                        }
+                        DataType::UInt64 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<UInt64Builder>()
+                            {
+                                let the_int: u64 = val.decode()?;
+                                builder.append_value(the_int);
+                            }
+                        }
+                        DataType::Decimal128(precision, scale) => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<Decimal128Builder>()
+                            {
+                                builder.append_value(decimal_value(val, *precision, *scale)?);
+                            }
+                        }
+                        DataType::Timestamp(unit, _) => {
+                            let the_time: i64 = val.decode()?;
+                            let builder = acc[idx].as_any_mut();
+                            match unit {
+                                TimeUnit::Second => builder
+                                    .downcast_mut::<TimestampSecondBuilder>()
+                                    .map(|builder| builder.append_value(the_time)),
+                                TimeUnit::Millisecond => builder
+                                    .downcast_mut::<TimestampMillisecondBuilder>()
+                                    .map(|builder| builder.append_value(the_time)),
+                                TimeUnit::Microsecond => builder
+                                    .downcast_mut::<TimestampMicrosecondBuilder>()
+                                    .map(|builder| builder.append_value(the_time)),
+                                TimeUnit::Nanosecond => builder
+                                    .downcast_mut::<TimestampNanosecondBuilder>()
+                                    .map(|builder| builder.append_value(the_time)),
+                            };
+                        }
                         DataType::Boolean => {
                             if let Some(builder) =
                                 acc[idx].as_any_mut().downcast_mut::<BooleanBuilder>()
@@ -99,6 +298,14 @@ pub fn to_arrow(term: Term, schema: arrow_schema::Schema) -> Result<Vec<ArrayRef
                                 builder.append_value(the_int);
                             }
                         }
+                        DataType::Float16 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<Float16Builder>()
+                            {
+                                let the_float: f64 = val.decode()?;
+                                builder.append_value(f16::from_f64(the_float));
+                            }
+                        }
                         DataType::Float32 => {
                             if let Some(builder) =
                                 acc[idx].as_any_mut().downcast_mut::<Float32Builder>()
@@ -107,6 +314,22 @@ pub fn to_arrow(term: Term, schema: arrow_schema::Schema) -> Result<Vec<ArrayRef
                                 builder.append_value(the_float);
                             }
                         }
+                        DataType::Float64 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<Float64Builder>()
+                            {
+                                let the_float: f64 = val.decode()?;
+                                builder.append_value(the_float);
+                            }
+                        }
+                        DataType::UInt8 => {
+                            if let Some(builder) =
+                                acc[idx].as_any_mut().downcast_mut::<UInt8Builder>()
+                            {
+                                let the_int: u8 = val.decode()?;
+                                builder.append_value(the_int);
+                            }
+                        }
                         DataType::Date64 => {
                             if let Some(builder) =
                                 acc[idx].as_any_mut().downcast_mut::<Date64Builder>()
@@ -173,70 +396,313 @@ pub fn to_arrow(term: Term, schema: arrow_schema::Schema) -> Result<Vec<ArrayRef
                                     builder.append(true);
                                 }
                             }
-                            _ => panic!("Unsupported data type {}", child.data_type()),
-                        },
-                        DataType::FixedSizeList(child, _dim) => match child.data_type() {
-                            DataType::Boolean => {
+                            DataType::Float16 => {
                                 if let Some(builder) = acc[idx]
                                     .as_any_mut()
-                                    .downcast_mut::<FixedSizeListBuilder<BooleanBuilder>>()
+                                    .downcast_mut::<ListBuilder<Float16Builder>>()
                                 {
-                                    let the_list: Vec<bool> = val.decode()?;
+                                    let the_list: Vec<f64> = val.decode()?;
                                     for s in the_list.iter() {
-                                        builder.values().append_value(*s);
+                                        builder.values().append_value(f16::from_f64(*s));
                                     }
                                     builder.append(true);
                                 }
                             }
-                            DataType::Utf8 => {
+                            DataType::Float64 => {
                                 if let Some(builder) = acc[idx]
                                     .as_any_mut()
-                                    .downcast_mut::<FixedSizeListBuilder<StringBuilder>>()
+                                    .downcast_mut::<ListBuilder<Float64Builder>>()
                                 {
-                                    let the_list: Vec<String> = val.decode()?;
+                                    let the_list: Vec<f64> = val.decode()?;
                                     for s in the_list.iter() {
-                                        builder.values().append_value(s.as_str());
+                                        builder.values().append_value(*s);
                                     }
                                     builder.append(true);
                                 }
                             }
-                            DataType::Int32 => {
+                            DataType::UInt8 => {
                                 if let Some(builder) = acc[idx]
                                     .as_any_mut()
-                                    .downcast_mut::<FixedSizeListBuilder<Int32Builder>>()
+                                    .downcast_mut::<ListBuilder<UInt8Builder>>()
                                 {
-                                    let the_list: Vec<i32> = val.decode()?;
+                                    let the_list: Vec<u8> = val.decode()?;
                                     for s in the_list.iter() {
                                         builder.values().append_value(*s);
                                     }
                                     builder.append(true);
                                 }
                             }
-                            DataType::Float32 => {
+                            DataType::Int64 => {
                                 if let Some(builder) = acc[idx]
                                     .as_any_mut()
-                                    .downcast_mut::<FixedSizeListBuilder<Float32Builder>>()
+                                    .downcast_mut::<ListBuilder<Int64Builder>>()
                                 {
-                                    let the_list: Vec<f32> = val.decode()?;
+                                    let the_list: Vec<i64> = val.decode()?;
                                     for s in the_list.iter() {
                                         builder.values().append_value(*s);
                                     }
                                     builder.append(true);
                                 }
                             }
-                            _ => panic!("Unsupported data type {}", child.data_type()),
+                            DataType::FixedSizeList(vector_field, dimension) => {
+                                let builder = acc[idx].as_any_mut();
+                                match vector_field.data_type() {
+                                    DataType::Float16 => {
+                                        if let Some(builder) = builder.downcast_mut::<
+                                            ListBuilder<FixedSizeListBuilder<Float16Builder>>,
+                                        >() {
+                                            let the_vectors: Vec<Vec<f64>> = val.decode()?;
+                                            append_vectors(
+                                                builder,
+                                                the_vectors,
+                                                *dimension,
+                                                field.name(),
+                                                |values, s| values.append_value(f16::from_f64(s)),
+                                            )?;
+                                        }
+                                    }
+                                    DataType::Float32 => {
+                                        if let Some(builder) = builder.downcast_mut::<
+                                            ListBuilder<FixedSizeListBuilder<Float32Builder>>,
+                                        >() {
+                                            let the_vectors: Vec<Vec<f32>> = val.decode()?;
+                                            append_vectors(
+                                                builder,
+                                                the_vectors,
+                                                *dimension,
+                                                field.name(),
+                                                |values, s| values.append_value(s),
+                                            )?;
+                                        }
+                                    }
+                                    DataType::Float64 => {
+                                        if let Some(builder) = builder.downcast_mut::<
+                                            ListBuilder<FixedSizeListBuilder<Float64Builder>>,
+                                        >() {
+                                            let the_vectors: Vec<Vec<f64>> = val.decode()?;
+                                            append_vectors(
+                                                builder,
+                                                the_vectors,
+                                                *dimension,
+                                                field.name(),
+                                                |values, s| values.append_value(s),
+                                            )?;
+                                        }
+                                    }
+                                    DataType::UInt8 => {
+                                        if let Some(builder) = builder.downcast_mut::<
+                                            ListBuilder<FixedSizeListBuilder<UInt8Builder>>,
+                                        >() {
+                                            let the_vectors: Vec<Vec<u8>> = val.decode()?;
+                                            append_vectors(
+                                                builder,
+                                                the_vectors,
+                                                *dimension,
+                                                field.name(),
+                                                |values, s| values.append_value(s),
+                                            )?;
+                                        }
+                                    }
+                                    _ => return Err(unsupported(field.data_type())),
+                                }
+                            }
+                            _ => return Err(unsupported(field.data_type())),
                         },
-                        _ => panic!("Unsupported data type {}", field.data_type()),
+                        DataType::FixedSizeList(child, dimension) => {
+                            check_dimension(val.list_length()?, *dimension, field.name())?;
+                            match child.data_type() {
+                                DataType::Boolean => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<BooleanBuilder>>()
+                                    {
+                                        let the_list: Vec<bool> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(*s);
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                DataType::Utf8 => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<StringBuilder>>()
+                                    {
+                                        let the_list: Vec<String> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(s.as_str());
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                DataType::Int32 => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<Int32Builder>>()
+                                    {
+                                        let the_list: Vec<i32> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(*s);
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                DataType::Float32 => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<Float32Builder>>()
+                                    {
+                                        let the_list: Vec<f32> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(*s);
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                DataType::Float16 => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<Float16Builder>>()
+                                    {
+                                        let the_list: Vec<f64> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(f16::from_f64(*s));
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                DataType::Float64 => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<Float64Builder>>()
+                                    {
+                                        let the_list: Vec<f64> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(*s);
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                DataType::UInt8 => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<UInt8Builder>>()
+                                    {
+                                        let the_list: Vec<u8> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(*s);
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                DataType::Int64 => {
+                                    if let Some(builder) = acc[idx]
+                                        .as_any_mut()
+                                        .downcast_mut::<FixedSizeListBuilder<Int64Builder>>()
+                                    {
+                                        let the_list: Vec<i64> = val.decode()?;
+                                        for s in the_list.iter() {
+                                            builder.values().append_value(*s);
+                                        }
+                                        builder.append(true);
+                                    }
+                                }
+                                _ => return Err(unsupported(field.data_type())),
+                            }
+                        }
+                        _ => return Err(unsupported(field.data_type())),
                     };
                 }
                 Ok(acc)
             });
 
     match builders {
-        Ok(mut builders) => {
-            let columns: Vec<ArrayRef> = builders.iter_mut().map(|b| b.finish()).collect();
-            Ok(columns)
-        }
+        Ok(mut builders) => builders
+            .iter_mut()
+            .zip(schema.fields())
+            .zip(struct_rows)
+            .map(|((builder, field), rows)| match field.data_type() {
+                DataType::Struct(children) => {
+                    let rows = rows.encode(term.get_env());
+                    struct_array(rows, children)
+                }
+                _ => Ok(builder.finish()),
+            })
+            .collect(),
         Err(err) => Err(Error::from(err)),
     }
 }
+
+fn unsupported(data_type: &DataType) -> Error {
+    Error::InvalidInput {
+        message: format!("Unsupported data type {}", data_type),
+    }
+}
+
+// A fixed size list builder panics on a wrong length, so rows are checked first
+fn check_dimension(len: usize, dimension: i32, field_name: &str) -> Result<()> {
+    if len != dimension as usize {
+        return Err(Error::InvalidInput {
+            message: format!(
+                "Expected {} dimensions in {}, got {}",
+                dimension, field_name, len
+            ),
+        });
+    }
+    Ok(())
+}
+
+// List<FixedSizeList<child>>, one fixed size list per vector of the row
+fn multi_vector_builder<T: ArrayBuilder>(
+    values: T,
+    child: &FieldRef,
+    vector_field: &FieldRef,
+    dimension: i32,
+) -> Box<dyn ArrayBuilder> {
+    Box::new(
+        ListBuilder::new(
+            FixedSizeListBuilder::new(values, dimension).with_field(vector_field.clone()),
+        )
+        .with_field(child.clone()),
+    )
+}
+
+fn append_vectors<T: ArrayBuilder, V: Copy>(
+    builder: &mut ListBuilder<FixedSizeListBuilder<T>>,
+    the_vectors: Vec<Vec<V>>,
+    dimension: i32,
+    field_name: &str,
+    append: impl Fn(&mut T, V),
+) -> Result<()> {
+    for vector in the_vectors.iter() {
+        check_dimension(vector.len(), dimension, field_name)?;
+        let vectors = builder.values();
+        for s in vector.iter() {
+            append(vectors.values(), *s);
+        }
+        vectors.append(true);
+    }
+    builder.append(true);
+    Ok(())
+}
+
+fn struct_array(rows: Term, children: &Fields) -> Result<ArrayRef> {
+    let columns = to_arrow(rows, Schema::new(children.clone()))?;
+    Ok(Arc::new(StructArray::try_new(
+        children.clone(),
+        columns,
+        None,
+    )?))
+}
+
+// Decimals are given as strings like "12.34", which hold any scale exactly, or as numbers
+fn decimal_value(val: Term, precision: u8, scale: i8) -> Result<i128> {
+    let text = match val.decode::<String>() {
+        Ok(text) => text,
+        Err(_) => match val.decode::<i64>() {
+            Ok(int) => int.to_string(),
+            Err(_) => val.decode::<f64>()?.to_string(),
+        },
+    };
+    Ok(parse_decimal::<Decimal128Type>(&text, precision, scale)?)
+}
@@ -4,14 +4,39 @@ use crate::{
     rustler_arrow::term_to_arrow::to_arrow,
 };
 use arrow_array::{RecordBatch, RecordBatchIterator};
-use rustler::{ResourceArc, Term};
+use lance::dataset::{WriteMode, WriteParams};
+use lancedb::{
+    connection::LanceFileVersion,
+    table::{AddDataMode as LanceAddDataMode, WriteOptions},
+};
+use rustler::{NifStruct, NifUnitEnum, ResourceArc, Term};
 
 use super::{table_conn, TableResource};
 
+#[derive(NifStruct, Debug)]
+#[module = "ElixirLanceDB.Native.Table.AddConfig"]
+pub struct AddConfig {
+    pub mode: AddMode,
+    pub max_rows_per_file: Option<usize>,
+    pub max_rows_per_group: Option<usize>,
+    pub data_storage_version: Option<String>,
+}
+
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum AddMode {
+    Append,
+    Overwrite,
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn add_records(table: ResourceArc<TableResource>, erl_data: Term) -> Result<()> {
+pub fn add_records(
+    table: ResourceArc<TableResource>,
+    erl_data: Term,
+    config: AddConfig,
+) -> Result<()> {
     // let arrow_schema = Arc::new(erl_schema.clone().into_arrow());
     let table = table_conn(table)?;
+    let write_params = config.write_params()?;
     get_runtime().block_on(async {
         let schema = table.schema().await?;
         let columnar_data = to_arrow(erl_data, (*schema).clone())?;
@@ -19,8 +44,55 @@ pub fn add_records(table: ResourceArc<TableResource>, erl_data: Term) -> Result<
             vec![RecordBatch::try_new(schema.clone(), columnar_data)],
             schema,
         );
-        table.add(batches).execute().await?;
+        table
+            .add(batches)
+            .mode(config.mode.into())
+            .write_options(WriteOptions {
+                lance_write_params: Some(write_params),
+            })
+            .execute()
+            .await?;
         Ok::<(), Error>(())
     })?;
     Ok(())
 }
+
+impl AddConfig {
+    // lancedb ignores the builder mode once write params are given, so it is set here too
+    fn write_params(&self) -> Result<WriteParams> {
+        let defaults = WriteParams::default();
+        let data_storage_version = self
+            .data_storage_version
+            .as_deref()
+            .map(|version| {
+                version
+                    .parse::<LanceFileVersion>()
+                    .map_err(|e| Error::InvalidInput {
+                        message: e.to_string(),
+                    })
+            })
+            .transpose()?;
+
+        Ok(WriteParams {
+            mode: match self.mode {
+                AddMode::Append => WriteMode::Append,
+                AddMode::Overwrite => WriteMode::Overwrite,
+            },
+            max_rows_per_file: self.max_rows_per_file.unwrap_or(defaults.max_rows_per_file),
+            max_rows_per_group: self
+                .max_rows_per_group
+                .unwrap_or(defaults.max_rows_per_group),
+            data_storage_version,
+            ..defaults
+        })
+    }
+}
+
+impl From<AddMode> for LanceAddDataMode {
+    fn from(value: AddMode) -> Self {
+        match value {
+            AddMode::Append => LanceAddDataMode::Append,
+            AddMode::Overwrite => LanceAddDataMode::Overwrite,
+        }
+    }
+}
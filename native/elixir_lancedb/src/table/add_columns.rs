@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use lancedb::table::NewColumnTransform as LanceNewColumnTransform;
+use lancedb::{table::NewColumnTransform as LanceNewColumnTransform, Table};
 // use lance::dataset::schema_evolution::NewColumnTransform as LanceNewColumnTransform;
 use rustler::{Decoder, ResourceArc};
 
@@ -8,25 +8,54 @@ use crate::{
     atoms,
     error::{Error, Result},
     runtime::get_runtime,
-    rustler_arrow::schema::Schema,
+    rustler_arrow::schema::{
+        field_type::{FieldType, TimeUnit},
+        Schema,
+    },
 };
 
-use super::{table_conn, TableResource};
+use super::{
+    batch_udf::{BatchUdfConfig, DirCheckpointStore},
+    filter::FilterValue,
+    table_conn, TableResource,
+};
 
+// `add_columns/2` in Elixir, which first starts the process serving a batch UDF function
 #[rustler::nif(schedule = "DirtyCpu")]
-fn add_columns(table: ResourceArc<TableResource>, transforms: NewColumnTransform) -> Result<()> {
+fn add_columns_with_transform(
+    table: ResourceArc<TableResource>,
+    transforms: NewColumnTransform,
+) -> Result<()> {
     let table = table_conn(table)?;
+    let (read_columns, checkpoint_dir) = match &transforms {
+        NewColumnTransform::BatchUdf(config) => {
+            (config.input_columns.clone(), config.checkpoint_dir.clone())
+        }
+        _ => (None, None),
+    };
     let result = get_runtime().block_on(async {
-        let result = table.add_columns(transforms.into(), None).await?;
+        let transforms = transforms.into_lance(&table).await?;
+        let result = table.add_columns(transforms, read_columns).await?;
 
         Ok::<(), Error>(result)
     })?;
 
+    if let Some(dir) = checkpoint_dir {
+        DirCheckpointStore::clear(dir)?;
+    }
     Ok(result)
 }
 
 pub enum NewColumnTransform {
     AllNulls(Schema),
+    // (column name, SQL expression over the existing columns)
+    SqlExpressions(Vec<(String, String)>),
+    Constant {
+        name: String,
+        field_type: FieldType,
+        value: FilterValue,
+    },
+    BatchUdf(BatchUdfConfig),
 }
 
 impl Decoder<'_> for NewColumnTransform {
@@ -37,17 +66,116 @@ impl Decoder<'_> for NewColumnTransform {
                 let schema: Schema = term.map_get(atoms::schema())?.decode()?;
                 Ok(NewColumnTransform::AllNulls(schema))
             }
-            _ => todo!("transform_type {} not supported", transform_type),
+            "sql_expressions" => {
+                let expressions: Vec<(String, String)> =
+                    term.map_get(atoms::expressions())?.decode()?;
+                Ok(NewColumnTransform::SqlExpressions(expressions))
+            }
+            "constant" => Ok(NewColumnTransform::Constant {
+                name: term.map_get(atoms::name())?.decode()?,
+                field_type: term.map_get(atoms::field_type())?.decode()?,
+                value: term.map_get(atoms::value())?.decode()?,
+            }),
+            "batch_udf" => Ok(NewColumnTransform::BatchUdf(term.decode()?)),
+            _ => Err(rustler::Error::RaiseTerm(Box::new(format!(
+                "transform_type {} not supported",
+                transform_type
+            )))),
         }
     }
 }
 
-impl Into<LanceNewColumnTransform> for NewColumnTransform {
-    fn into(self) -> LanceNewColumnTransform {
-        match self {
+impl NewColumnTransform {
+    async fn into_lance(self, table: &Table) -> Result<LanceNewColumnTransform> {
+        let transform = match self {
             NewColumnTransform::AllNulls(schema) => {
                 LanceNewColumnTransform::AllNulls(Arc::new(schema.into()))
             }
-        }
+            NewColumnTransform::SqlExpressions(expressions) => {
+                LanceNewColumnTransform::SqlExpressions(expressions)
+            }
+            // a literal cast to the column's type, evaluated once per row
+            NewColumnTransform::Constant {
+                name,
+                field_type,
+                value,
+            } => {
+                let literal = match value {
+                    FilterValue::List(_) => {
+                        return Err(Error::InvalidInput {
+                            message: "constant column defaults must be scalar values".to_string(),
+                        })
+                    }
+                    value => value.to_sql()?,
+                };
+                let expression = format!("CAST({} AS {})", literal, sql_type(&field_type)?);
+                LanceNewColumnTransform::SqlExpressions(vec![(name, expression)])
+            }
+            // checkpoints are keyed by the table and the version the backfill starts from
+            NewColumnTransform::BatchUdf(config) => {
+                check_udf_input(table, config.input_columns.as_ref()).await?;
+                let version = table.version().await?;
+                LanceNewColumnTransform::BatchUDF(config.into_udf(table.dataset_uri(), version)?)
+            }
+        };
+        Ok(transform)
     }
 }
+
+// Lance hands the function every column when no input columns are given, so each
+// column it reads must convert to Elixir terms before the backfill starts
+async fn check_udf_input(table: &Table, input_columns: Option<&Vec<String>>) -> Result<()> {
+    let schema = table.schema().await?;
+    let unsupported = schema.fields().iter().find(|field| {
+        input_columns.map_or(true, |columns| columns.contains(field.name()))
+            && FieldType::try_from(field.data_type()).is_err()
+    });
+    match unsupported {
+        Some(field) => Err(Error::InvalidInput {
+            message: format!(
+                "column {} of type {} can't be passed to the add_columns function, \
+                 give input_columns without it",
+                field.name(),
+                field.data_type()
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn sql_type(field_type: &FieldType) -> Result<String> {
+    let sql_type = match field_type {
+        FieldType::Boolean => "BOOLEAN",
+        FieldType::Utf8 => "STRING",
+        FieldType::Binary => "BINARY",
+        FieldType::Float32 => "FLOAT",
+        FieldType::Float64 => "DOUBLE",
+        FieldType::Int8 => "TINYINT",
+        FieldType::Int16 => "SMALLINT",
+        FieldType::Int32 => "INT",
+        FieldType::Int64 => "BIGINT",
+        FieldType::Uint8 => "TINYINT UNSIGNED",
+        FieldType::Uint16 => "SMALLINT UNSIGNED",
+        FieldType::Uint32 => "INT UNSIGNED",
+        FieldType::Uint64 => "BIGINT UNSIGNED",
+        FieldType::Date32 => "DATE",
+        FieldType::Decimal128(precision, scale) => {
+            return Ok(format!("DECIMAL({}, {})", precision, scale))
+        }
+        FieldType::Timestamp(unit) => {
+            let precision = match unit {
+                TimeUnit::Second => 0,
+                TimeUnit::Millisecond => 3,
+                TimeUnit::Microsecond => 6,
+                TimeUnit::Nanosecond => 9,
+            };
+            return Ok(format!("TIMESTAMP({})", precision));
+        }
+        other => {
+            return Err(Error::InvalidInput {
+                message: format!("constant column defaults are not supported for {:?}", other),
+            })
+        }
+    };
+    Ok(sql_type.to_string())
+}
@@ -4,22 +4,67 @@ use crate::{
 };
 
 use super::{super::rustler_arrow::schema::field_type::FieldType, table_conn, TableResource};
-use lancedb::table::ColumnAlteration as LanceColumnAlteration;
-use rustler::{NifStruct, ResourceArc};
+use arrow_array::{cast::AsArray, types::Float64Type, Array, ArrayRef, RecordBatch};
+use arrow_cast::{can_cast_types, cast, cast_with_options, CastOptions};
+use arrow_schema::{DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema};
+use futures::{future, TryStreamExt};
+use lancedb::{
+    query::{ExecutableQuery, QueryBase, Select},
+    table::ColumnAlteration as LanceColumnAlteration,
+    Table,
+};
+use rustler::{Encoder, Env, NifMap, NifStruct, NifUnitEnum, ResourceArc, Term};
+
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum AlterMode {
+    Commit,
+    // checks the casts against the current values without altering anything
+    DryRun,
+}
+
+pub enum AlterReply {
+    Committed,
+    DryRun(Vec<CastReport>),
+}
+
+#[derive(NifMap)]
+#[rustler(encode)]
+pub struct CastReport {
+    pub path: String,
+    pub num_values: u64,
+    pub num_failed: u64,
+    pub num_overflowed: u64,
+}
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn alter_columns(
     table: ResourceArc<TableResource>,
     alterations: Vec<ColumnAlteration>,
-) -> Result<()> {
+    mode: AlterMode,
+) -> Result<AlterReply> {
     let table = table_conn(table)?;
-    let result = get_runtime().block_on(async {
+    get_runtime().block_on(async {
+        let schema = table.schema().await?;
+        for alteration in &alterations {
+            alteration.validate(&schema)?;
+        }
+
+        if let AlterMode::DryRun = mode {
+            let mut reports = vec![];
+            for alteration in alterations {
+                if let Some(field_type) = alteration.data_type {
+                    reports
+                        .push(CastReport::check(&table, alteration.path, field_type.into()).await?);
+                }
+            }
+            return Ok(AlterReply::DryRun(reports));
+        }
+
         let lance_alterations: Vec<LanceColumnAlteration> =
             alterations.into_iter().map(|a| a.into()).collect();
-        Ok::<(), Error>(table.alter_columns(&lance_alterations).await?)
-    })?;
-
-    Ok(result)
+        table.alter_columns(&lance_alterations).await?;
+        Ok::<AlterReply, Error>(AlterReply::Committed)
+    })
 }
 
 #[derive(NifStruct)]
@@ -31,6 +76,82 @@ pub struct ColumnAlteration {
     pub data_type: Option<FieldType>,
 }
 
+impl ColumnAlteration {
+    // Lance renames and relaxes nested fields in place, but can only cast top-level columns
+    fn validate(&self, schema: &ArrowSchema) -> Result<()> {
+        let ancestry = resolve(schema, &self.path)?;
+        let Some(field_type) = &self.data_type else {
+            return Ok(());
+        };
+        if ancestry.len() > 1 {
+            return Err(Error::InvalidInput {
+                message: format!(
+                    "cannot cast nested field \"{}\", only rename and nullable apply to it",
+                    self.path
+                ),
+            });
+        }
+
+        let from = ancestry[0].data_type();
+        let to: ArrowDataType = field_type.clone().into();
+        if !(can_cast_types(from, &to) && same_family(from, &to)) {
+            return Err(Error::InvalidInput {
+                message: format!(
+                    "cannot cast column \"{}\" from {:?} to {:?}",
+                    self.path, from, to
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl CastReport {
+    // folds the counts over the column a batch at a time, so it is never held in memory whole
+    async fn check(table: &Table, path: String, data_type: ArrowDataType) -> Result<CastReport> {
+        let report = CastReport {
+            path: path.clone(),
+            num_values: 0,
+            num_failed: 0,
+            num_overflowed: 0,
+        };
+        table
+            .query()
+            .select(Select::Columns(vec![path]))
+            .execute()
+            .await?
+            .map_err(Error::from)
+            .try_fold(report, |report, batch| {
+                future::ready(report.count(&batch, &data_type))
+            })
+            .await
+    }
+
+    fn count(mut self, batch: &RecordBatch, data_type: &ArrowDataType) -> Result<CastReport> {
+        // a safe cast turns each value the committed cast would reject into a null
+        let options = CastOptions {
+            safe: true,
+            ..Default::default()
+        };
+        let from = batch.column(0);
+        let to = cast_with_options(from, data_type, &options)?;
+        let (failed, overflowed) = count_lossy(from, &to)?;
+        self.num_values += (from.len() - from.null_count()) as u64;
+        self.num_failed += failed;
+        self.num_overflowed += overflowed;
+        Ok(self)
+    }
+}
+
+impl Encoder for AlterReply {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            AlterReply::Committed => ().encode(env),
+            AlterReply::DryRun(reports) => reports.encode(env),
+        }
+    }
+}
+
 impl Into<LanceColumnAlteration> for ColumnAlteration {
     fn into(self) -> LanceColumnAlteration {
         LanceColumnAlteration {
@@ -44,3 +165,108 @@ impl Into<LanceColumnAlteration> for ColumnAlteration {
         }
     }
 }
+
+// The fields along a dotted path, outermost first
+fn resolve<'a>(schema: &'a ArrowSchema, path: &str) -> Result<Vec<&'a ArrowField>> {
+    let mut fields = schema.fields();
+    let mut ancestry: Vec<&ArrowField> = vec![];
+    for name in path.split('.') {
+        if let Some(parent) = ancestry.last() {
+            match parent.data_type() {
+                ArrowDataType::Struct(children) => fields = children,
+                _ => {
+                    return Err(Error::InvalidInput {
+                        message: format!("\"{}\" in \"{}\" is not a struct", parent.name(), path),
+                    })
+                }
+            }
+        }
+        let field = fields
+            .iter()
+            .find(|field| field.name() == name)
+            .ok_or_else(|| Error::InvalidInput {
+                message: format!("column \"{}\" does not exist", path),
+            })?;
+        ancestry.push(field.as_ref());
+    }
+    Ok(ancestry)
+}
+
+// Mirrors Lance's own check, so a dry run rejects the casts a commit would
+fn same_family(from: &ArrowDataType, to: &ArrowDataType) -> bool {
+    use ArrowDataType::*;
+    match from {
+        from if from.is_integer() => to.is_integer(),
+        from if from.is_floating() => to.is_floating(),
+        from if from.is_temporal() => to.is_temporal(),
+        Boolean => matches!(to, Boolean),
+        Utf8 | LargeUtf8 => matches!(to, Utf8 | LargeUtf8),
+        Binary | LargeBinary => matches!(to, Binary | LargeBinary),
+        Decimal128(_, _) | Decimal256(_, _) => matches!(to, Decimal128(_, _) | Decimal256(_, _)),
+        List(from) | LargeList(from) | FixedSizeList(from, _) => match to {
+            List(to) | LargeList(to) | FixedSizeList(to, _) => {
+                same_family(from.data_type(), to.data_type())
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// (values rejected by the cast, numeric values outside the target's range).
+// Out of range integers and decimals are rejected, while out of range floats
+// become infinite and are only counted as overflowed.
+fn count_lossy(from: &dyn Array, to: &dyn Array) -> Result<(u64, u64)> {
+    let mut failed = (0..from.len())
+        .filter(|&i| from.is_valid(i) && to.is_null(i))
+        .count() as u64;
+
+    let overflowed = match (list_values(from), list_values(to)) {
+        (Some(from), Some(to)) if from.len() == to.len() => {
+            let (child_failed, child_overflowed) = count_lossy(from.as_ref(), to.as_ref())?;
+            failed += child_failed;
+            child_overflowed
+        }
+        _ if from.data_type().is_numeric() && to.data_type().is_floating() => {
+            let from = cast(from, &ArrowDataType::Float64)?;
+            let to = cast(to, &ArrowDataType::Float64)?;
+            from.as_primitive::<Float64Type>()
+                .iter()
+                .zip(to.as_primitive::<Float64Type>().iter())
+                .filter(
+                    |pair| matches!(pair, (Some(f), Some(t)) if f.is_finite() && t.is_infinite()),
+                )
+                .count() as u64
+        }
+        _ if from.data_type().is_numeric() && to.data_type().is_numeric() => failed,
+        _ => 0,
+    };
+    Ok((failed, overflowed))
+}
+
+// Only the values the list's offsets cover, since a sliced list shares its parent's values
+fn list_values(array: &dyn Array) -> Option<ArrayRef> {
+    match array.data_type() {
+        ArrowDataType::List(_) => {
+            let list = array.as_list::<i32>();
+            let offsets = list.value_offsets();
+            let (start, end) = (offsets[0] as usize, offsets[list.len()] as usize);
+            Some(list.values().slice(start, end - start))
+        }
+        ArrowDataType::LargeList(_) => {
+            let list = array.as_list::<i64>();
+            let offsets = list.value_offsets();
+            let (start, end) = (offsets[0] as usize, offsets[list.len()] as usize);
+            Some(list.values().slice(start, end - start))
+        }
+        ArrowDataType::FixedSizeList(_, _) => {
+            let list = array.as_fixed_size_list();
+            let start = list.value_offset(0) as usize;
+            Some(
+                list.values()
+                    .slice(start, list.len() * list.value_length() as usize),
+            )
+        }
+        _ => None,
+    }
+}
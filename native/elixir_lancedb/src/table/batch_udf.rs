@@ -0,0 +1,339 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, RecvTimeoutError, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use arrow_array::RecordBatch;
+use arrow_ipc::{reader::FileReader, writer::FileWriter};
+use arrow_schema::{ArrowError, SchemaRef};
+use lance::{
+    dataset::{BatchInfo, BatchUDF, UDFCheckpointStore},
+    table::format::Fragment,
+};
+use rustler::{resource_impl, Encoder, LocalPid, NifStruct, OwnedEnv, Resource, ResourceArc, Term};
+
+use crate::{
+    atoms,
+    error::{Error, Result},
+    rustler_arrow::{schema::Schema, term_from_arrow::from_arrow, term_to_arrow::to_arrow},
+};
+
+// Handed to Elixir with each batch of input rows; replying through it wakes the waiting backfill
+pub struct BatchUdfReplyResource {
+    schema: SchemaRef,
+    sender: Mutex<Option<SyncSender<std::result::Result<RecordBatch, String>>>>,
+}
+
+#[resource_impl]
+impl Resource for BatchUdfReplyResource {}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn add_columns_reply(reply: ResourceArc<BatchUdfReplyResource>, rows: Term) -> Result<()> {
+    let sender = take_sender(&reply)?;
+    let timed_out = || Error::InvalidInput {
+        message: "this add_columns batch has already timed out".to_string(),
+    };
+    let schema = reply.schema.as_ref().clone();
+    let batch = to_arrow(rows, schema)
+        .and_then(|columns| Ok(RecordBatch::try_new(reply.schema.clone(), columns)?));
+    match batch {
+        Ok(batch) => sender.send(Ok(batch)).map_err(|_| timed_out()),
+        Err(error) => {
+            // fail the backfill now rather than leaving it to time out
+            let message = "the add_columns reply did not match the output schema".to_string();
+            sender.send(Err(message)).map_err(|_| timed_out())?;
+            Err(error)
+        }
+    }
+}
+
+// Fails the backfill with the error the Elixir function raised for this batch
+#[rustler::nif]
+pub fn add_columns_reply_error(
+    reply: ResourceArc<BatchUdfReplyResource>,
+    message: String,
+) -> Result<()> {
+    let sender = take_sender(&reply)?;
+    sender
+        .send(Err(format!("the add_columns function failed: {}", message)))
+        .map_err(|_| Error::InvalidInput {
+            message: "this add_columns batch has already timed out".to_string(),
+        })
+}
+
+fn take_sender(
+    reply: &BatchUdfReplyResource,
+) -> Result<SyncSender<std::result::Result<RecordBatch, String>>> {
+    reply
+        .sender
+        .lock()
+        .map_err(|_| Error::Other {
+            message: "add_columns reply lock poisoned".to_string(),
+        })?
+        .take()
+        .ok_or_else(|| Error::InvalidInput {
+            message: "this add_columns batch has already been answered".to_string(),
+        })
+}
+
+#[derive(NifStruct, Clone)]
+#[module = "ElixirLanceDB.Native.Schema.NewColumnTransform.BatchUDF"]
+pub struct BatchUdfConfig {
+    pub pid: LocalPid,
+    pub output_schema: Schema,
+    pub input_columns: Option<Vec<String>>,
+    pub checkpoint_dir: Option<String>,
+    pub timeout: u64,
+}
+
+impl BatchUdfConfig {
+    pub fn into_udf(self, table_uri: &str, version: u64) -> Result<BatchUDF> {
+        let output_schema: SchemaRef = Arc::new(self.output_schema.into());
+        let result_checkpoint = match self.checkpoint_dir {
+            None => None,
+            Some(dir) => {
+                let manifest =
+                    Manifest::new(table_uri, version, &output_schema, self.input_columns);
+                let store: Arc<dyn UDFCheckpointStore> =
+                    Arc::new(DirCheckpointStore::open(dir, &manifest)?);
+                Some(store)
+            }
+        };
+        let callback = CallbackUdf {
+            pid: self.pid,
+            output_schema: output_schema.clone(),
+            timeout: Duration::from_millis(self.timeout),
+        };
+
+        Ok(BatchUDF {
+            mapper: Box::new(move |batch| callback.map(batch)),
+            output_schema,
+            result_checkpoint,
+        })
+    }
+}
+
+// Sends each batch of input rows to an Elixir process as
+// `{:elixir_lancedb_add_columns, reply, rows}` and builds the new columns from
+// the rows it replies with, one per input row.
+struct CallbackUdf {
+    pid: LocalPid,
+    output_schema: SchemaRef,
+    timeout: Duration,
+}
+
+impl CallbackUdf {
+    fn map(&self, batch: &RecordBatch) -> lance::Result<RecordBatch> {
+        let rows = from_arrow(vec![batch.clone()])
+            .map_err(|_| udf_error("failed converting add_columns input rows".to_string()))?;
+
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let reply = ResourceArc::new(BatchUdfReplyResource {
+            schema: self.output_schema.clone(),
+            sender: Mutex::new(Some(sender)),
+        });
+        let pid = self.pid;
+
+        // OwnedEnv can't send from a scheduler thread, which is where the backfill runs
+        thread::spawn(move || {
+            OwnedEnv::new().send_and_clear(&pid, |env| {
+                (atoms::elixir_lancedb_add_columns(), reply, rows).encode(env)
+            })
+        })
+        .join()
+        .map_err(|_| udf_error("failed sending add_columns input rows".to_string()))?
+        .map_err(|_| udf_error("the add_columns process is not alive".to_string()))?;
+
+        let new_columns = match receiver.recv_timeout(self.timeout) {
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(udf_error(format!(
+                    "the add_columns process did not reply within {}ms",
+                    self.timeout.as_millis()
+                )))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(udf_error(
+                    "the add_columns batch was dropped without a reply".to_string(),
+                ))
+            }
+            Ok(reply) => reply.map_err(udf_error)?,
+        };
+
+        if new_columns.num_rows() != batch.num_rows() {
+            return Err(udf_error(format!(
+                "expected {} add_columns rows but received {}",
+                batch.num_rows(),
+                new_columns.num_rows()
+            )));
+        }
+        Ok(new_columns)
+    }
+}
+
+// Keeps finished batches as Arrow IPC files and finished fragments as JSON, so
+// running the same backfill again skips the work already done. The checkpoints are
+// only keyed by fragment and batch, so a manifest of the backfill they belong to is
+// kept beside them and checked before any is reused.
+pub struct DirCheckpointStore {
+    dir: PathBuf,
+}
+
+// What a backfill reads and writes, as far as its checkpoints depend on it
+#[derive(PartialEq)]
+struct Manifest(serde_json::Value);
+
+impl Manifest {
+    fn new(
+        table_uri: &str,
+        version: u64,
+        output_schema: &SchemaRef,
+        input_columns: Option<Vec<String>>,
+    ) -> Self {
+        let fields: Vec<serde_json::Value> = output_schema
+            .fields()
+            .iter()
+            .map(|field| {
+                serde_json::json!({
+                    "name": field.name(),
+                    "data_type": field.data_type().to_string(),
+                    "nullable": field.is_nullable(),
+                })
+            })
+            .collect();
+        Manifest(serde_json::json!({
+            "table_uri": table_uri,
+            "version": version,
+            "output_schema": fields,
+            "input_columns": input_columns,
+        }))
+    }
+}
+
+impl DirCheckpointStore {
+    fn open(dir: impl Into<PathBuf>, manifest: &Manifest) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let store = DirCheckpointStore { dir };
+
+        let path = store.manifest_path();
+        if path.exists() {
+            let found = serde_json::from_str(&fs::read_to_string(&path)?)
+                .map(Manifest)
+                .map_err(|e| Error::Other {
+                    message: format!("unreadable checkpoint manifest: {}", e),
+                })?;
+            if found != *manifest {
+                return Err(Error::InvalidInput {
+                    message: format!(
+                        "{} holds checkpoints of a backfill of another table or table version, \
+                         or with different output fields or input columns, clear it or use \
+                         another checkpoint_dir",
+                        store.dir.display()
+                    ),
+                });
+            }
+        } else {
+            let json = manifest.0.to_string();
+            store
+                .write_atomic(path, |mut file| {
+                    file.write_all(json.as_bytes()).map_err(io_error)
+                })
+                .map_err(Error::from)?;
+        }
+        Ok(store)
+    }
+
+    // called once the new columns are committed, as the checkpoints only apply to that backfill
+    pub fn clear(dir: impl AsRef<Path>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            if name.starts_with("batch-") || name.starts_with("fragment-") || name == MANIFEST {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn batch_path(&self, info: &BatchInfo) -> PathBuf {
+        self.dir.join(format!(
+            "batch-{}-{}.arrow",
+            info.fragment_id, info.batch_index
+        ))
+    }
+
+    fn fragment_path(&self, fragment_id: u32) -> PathBuf {
+        self.dir.join(format!("fragment-{}.json", fragment_id))
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.dir.join(MANIFEST)
+    }
+
+    // written beside the target and renamed, so an interruption never leaves a partial checkpoint
+    fn write_atomic(
+        &self,
+        path: PathBuf,
+        write: impl FnOnce(File) -> lance::Result<()>,
+    ) -> lance::Result<()> {
+        let partial = path.with_extension("partial");
+        write(File::create(&partial).map_err(io_error)?)?;
+        fs::rename(partial, path).map_err(io_error)
+    }
+}
+
+const MANIFEST: &str = "manifest.json";
+
+impl UDFCheckpointStore for DirCheckpointStore {
+    fn get_batch(&self, info: &BatchInfo) -> lance::Result<Option<RecordBatch>> {
+        let path = self.batch_path(info);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let mut reader = FileReader::try_new(File::open(path).map_err(io_error)?, None)?;
+        Ok(reader.next().transpose()?)
+    }
+
+    fn insert_batch(&self, info: BatchInfo, batch: RecordBatch) -> lance::Result<()> {
+        self.write_atomic(self.batch_path(&info), |file| {
+            let mut writer = FileWriter::try_new(BufWriter::new(file), &batch.schema())?;
+            writer.write(&batch)?;
+            Ok(writer.finish()?)
+        })
+    }
+
+    fn get_fragment(&self, fragment_id: u32) -> lance::Result<Option<Fragment>> {
+        let path = self.fragment_path(fragment_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(path).map_err(io_error)?;
+        let fragment = serde_json::from_str(&json).map_err(|e| udf_error(e.to_string()))?;
+        Ok(Some(fragment))
+    }
+
+    fn insert_fragment(&self, fragment: Fragment) -> lance::Result<()> {
+        let json = serde_json::to_string(&fragment).map_err(|e| udf_error(e.to_string()))?;
+        self.write_atomic(self.fragment_path(fragment.id as u32), |mut file| {
+            file.write_all(json.as_bytes()).map_err(io_error)
+        })
+    }
+}
+
+fn udf_error(message: String) -> lance::Error {
+    ArrowError::ExternalError(message.into()).into()
+}
+
+fn io_error(error: std::io::Error) -> lance::Error {
+    udf_error(error.to_string())
+}
@@ -1,14 +1,66 @@
-use rustler::ResourceArc;
+use std::collections::HashMap;
 
-use crate::{error::Result, runtime::get_runtime};
+use rustler::{NifMap, ResourceArc};
 
-use super::{table_conn, TableResource};
+use crate::{
+    error::{Error, Result},
+    runtime::get_runtime,
+    rustler_arrow::term_from_arrow::ReturnableTerm,
+};
+
+use super::{
+    filter::Filter,
+    not_native,
+    returning::{Captured, Returning},
+    table_conn, TableResource,
+};
+
+#[derive(NifMap)]
+#[rustler(encode)]
+pub struct DeleteResult {
+    pub num_deleted_rows: u64,
+    pub version: u64,
+    pub row_ids: Option<Vec<u64>>,
+    pub rows: Option<Vec<HashMap<String, ReturnableTerm>>>,
+}
 
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn delete(table: ResourceArc<TableResource>, predicate: String) -> Result<()> {
+pub fn delete(
+    table: ResourceArc<TableResource>,
+    predicate: Filter,
+    returning: Returning,
+) -> Result<DeleteResult> {
     let table = table_conn(table)?;
+    let predicate: String = predicate.try_into()?;
+
+    get_runtime().block_on(async {
+        // Lance does not report how many rows a delete removes, so they are
+        // counted or captured just before it, as of the version it deletes from
+        if let Some(wrapper) = table.dataset() {
+            // held across the read and the delete so both see the same version
+            let mut dataset = wrapper.get_mut().await?;
+            let captured = Captured::read(&dataset, Some(&predicate), returning).await?;
+            dataset.delete(&predicate).await?;
 
-    get_runtime().block_on(async { table.delete(predicate.as_str()).await })?;
+            return Ok(DeleteResult {
+                num_deleted_rows: captured.num_rows,
+                version: dataset.version().version,
+                row_ids: captured.row_ids,
+                rows: captured.rows,
+            });
+        }
+        if returning != Returning::Count {
+            return Err(not_native());
+        }
 
-    Ok(())
+        // a remote table is counted separately, so a concurrent write may change the count
+        let num_deleted_rows = table.count_rows(Some(predicate.clone())).await? as u64;
+        let result = table.delete(&predicate).await?;
+        Ok::<DeleteResult, Error>(DeleteResult {
+            num_deleted_rows,
+            version: result.version,
+            row_ids: None,
+            rows: None,
+        })
+    })
 }
@@ -0,0 +1,414 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arrow_schema::{DataType, Schema as ArrowSchema};
+use datafusion_expr::{utils::split_conjunction, Expr};
+use lance::datatypes::Schema as LanceSchema;
+use lance_datafusion::{
+    logical_expr::{coerce_filter_type_to_boolean, resolve_expr},
+    planner::Planner,
+};
+use rustler::{
+    types::atom, Decoder, NifMap, NifResult, NifStruct, NifUnitEnum, ResourceArc, Term, TermType,
+};
+
+use crate::{
+    atoms,
+    error::{Error, Result},
+    runtime::get_runtime,
+};
+
+use super::{table_conn, TableResource};
+
+// Plans the filter the way Lance would, without running it. An empty list means it is valid.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn validate_filter(
+    table: ResourceArc<TableResource>,
+    filter: Filter,
+) -> Result<Vec<FilterIssue>> {
+    let sql: String = filter.try_into()?;
+    let table = table_conn(table)?;
+    let schema = get_runtime().block_on(async { table.schema().await })?;
+
+    // parsed without a schema so unknown columns survive to be reported one by one
+    let parsed = match Planner::new(Arc::new(ArrowSchema::empty())).parse_filter(&sql) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Ok(vec![FilterIssue {
+                columns: vec![],
+                reason: FilterIssueReason::Syntax,
+                message: e.to_string(),
+            }])
+        }
+    };
+
+    let lance_schema = LanceSchema::try_from(schema.as_ref()).map_err(|e| Error::Other {
+        message: e.to_string(),
+    })?;
+    let planner = Planner::new(schema.clone());
+    let mut issues: Vec<FilterIssue> = vec![];
+
+    for conjunct in split_conjunction(&parsed) {
+        let columns = Planner::column_names_in_expr(conjunct);
+        let unknown: Vec<&String> = columns
+            .iter()
+            .filter(|column| !has_field(&schema, column))
+            .collect();
+
+        if !unknown.is_empty() {
+            for column in unknown {
+                if !issues.iter().any(|issue| issue.columns.contains(column)) {
+                    issues.push(FilterIssue {
+                        columns: vec![column.clone()],
+                        reason: FilterIssueReason::UnknownColumn,
+                        message: format!("no column named {} in the table schema", column),
+                    });
+                }
+            }
+            continue;
+        }
+
+        if let Err(message) = type_check(&planner, &lance_schema, &schema, conjunct) {
+            issues.push(FilterIssue {
+                columns,
+                reason: FilterIssueReason::TypeMismatch,
+                message,
+            });
+        }
+    }
+
+    Ok(issues)
+}
+
+#[derive(NifMap, Debug)]
+pub struct FilterIssue {
+    pub columns: Vec<String>,
+    pub reason: FilterIssueReason,
+    pub message: String,
+}
+
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum FilterIssueReason {
+    Syntax,
+    UnknownColumn,
+    TypeMismatch,
+}
+
+fn type_check(
+    planner: &Planner,
+    lance_schema: &LanceSchema,
+    schema: &ArrowSchema,
+    expr: &Expr,
+) -> std::result::Result<(), String> {
+    let resolved = resolve_expr(expr, lance_schema)
+        .and_then(coerce_filter_type_to_boolean)
+        .map_err(|e| e.to_string())?;
+    let optimized = planner.optimize_expr(resolved).map_err(|e| e.to_string())?;
+    let physical = planner
+        .create_physical_expr(&optimized)
+        .map_err(|e| e.to_string())?;
+
+    match physical.data_type(schema).map_err(|e| e.to_string())? {
+        DataType::Boolean | DataType::Null => Ok(()),
+        other => Err(format!("expected a boolean expression, got {}", other)),
+    }
+}
+
+// Columns may be nested struct paths such as `meta.author`
+fn has_field(schema: &ArrowSchema, path: &str) -> bool {
+    let mut parts = path.split('.');
+    let Some(Ok(mut field)) = parts.next().map(|name| schema.field_with_name(name)) else {
+        return false;
+    };
+    for name in parts {
+        match field.data_type() {
+            DataType::Struct(children) => match children.iter().find(|f| f.name() == name) {
+                Some(child) => field = child.as_ref(),
+                None => return false,
+            },
+            _ => return false,
+        }
+    }
+    true
+}
+
+// A filter is either raw SQL or SQL with `?` / `$name` placeholders whose
+// values are rendered as literals here, never interpolated by the caller.
+#[derive(Clone, Debug)]
+pub enum Filter {
+    Sql(String),
+    Parameterized(ParameterizedFilter),
+}
+
+#[derive(NifStruct, Clone, Debug)]
+#[rustler(decode)]
+#[module = "ElixirLanceDB.Native.Table.Filter"]
+pub struct ParameterizedFilter {
+    pub sql: String,
+    pub params: Vec<FilterValue>,
+    pub named_params: HashMap<String, FilterValue>,
+}
+
+#[derive(Clone, Debug)]
+pub enum FilterValue {
+    Null,
+    Boolean(bool),
+    Int64(i64),
+    Float64(f64),
+    Utf8(String),
+    Date32 {
+        year: i32,
+        month: u32,
+        day: u32,
+    },
+    Timestamp {
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+        microsecond: u32,
+        // only UTC renders, checked when the filter is bound
+        time_zone: Option<String>,
+    },
+    // rendered as a parenthesized list for `IN`
+    List(Vec<FilterValue>),
+}
+
+impl Decoder<'_> for Filter {
+    fn decode(term: Term) -> NifResult<Self> {
+        if term.is_binary() {
+            return Ok(Filter::Sql(term.decode()?));
+        }
+        Ok(Filter::Parameterized(term.decode()?))
+    }
+}
+
+impl Decoder<'_> for FilterValue {
+    fn decode(term: Term) -> NifResult<Self> {
+        let value = match term.get_type() {
+            TermType::Atom if term == atom::nil().to_term(term.get_env()) => FilterValue::Null,
+            TermType::Atom => FilterValue::Boolean(term.decode()?),
+            TermType::Integer => FilterValue::Int64(term.decode()?),
+            TermType::Float => FilterValue::Float64(term.decode()?),
+            TermType::Binary => FilterValue::Utf8(term.decode()?),
+            TermType::List => FilterValue::List(term.decode()?),
+            TermType::Map => decode_calendar_value(term)?,
+            _ => return Err(rustler::Error::BadArg),
+        };
+        Ok(value)
+    }
+}
+
+fn decode_calendar_value(term: Term) -> NifResult<FilterValue> {
+    let module = term.map_get(atom::__struct__())?.atom_to_string()?;
+    let year: i32 = term.map_get(atoms::year())?.decode()?;
+    let month: u32 = term.map_get(atoms::month())?.decode()?;
+    let day: u32 = term.map_get(atoms::day())?.decode()?;
+
+    match module.as_str() {
+        "Elixir.Date" => Ok(FilterValue::Date32 { year, month, day }),
+        "Elixir.NaiveDateTime" | "Elixir.DateTime" => {
+            let time_zone = match module.as_str() {
+                "Elixir.DateTime" => Some(term.map_get(atoms::time_zone())?.decode()?),
+                _ => None,
+            };
+            let (microsecond, _precision): (u32, u32) =
+                term.map_get(atoms::microsecond())?.decode()?;
+            Ok(FilterValue::Timestamp {
+                year,
+                month,
+                day,
+                hour: term.map_get(atoms::hour())?.decode()?,
+                minute: term.map_get(atoms::minute())?.decode()?,
+                second: term.map_get(atoms::second())?.decode()?,
+                microsecond,
+                time_zone,
+            })
+        }
+        _ => Err(rustler::Error::BadArg),
+    }
+}
+
+impl FilterValue {
+    pub(super) fn to_sql(&self) -> Result<String> {
+        let sql = match self {
+            FilterValue::Null => "NULL".to_string(),
+            FilterValue::Boolean(value) => value.to_string().to_uppercase(),
+            // parenthesized so `price -?` cannot render as a `--` comment
+            FilterValue::Int64(value) if *value < 0 => format!("({})", value),
+            FilterValue::Int64(value) => value.to_string(),
+            FilterValue::Float64(value) if !value.is_finite() => {
+                return Err(Error::InvalidInput {
+                    message: format!("float filter bindings must be finite, got {}", value),
+                })
+            }
+            FilterValue::Float64(value) if value.is_sign_negative() => format!("({:?})", value),
+            FilterValue::Float64(value) => format!("{:?}", value),
+            // Lance's SQL dialect takes backslashes literally, so only quotes need escaping
+            FilterValue::Utf8(value) => format!("'{}'", value.replace('\'', "''")),
+            FilterValue::Date32 { year, month, day } => {
+                format!("date '{:04}-{:02}-{:02}'", year, month, day)
+            }
+            FilterValue::Timestamp {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                microsecond,
+                time_zone,
+            } => {
+                if let Some(time_zone) = time_zone.as_ref().filter(|tz| *tz != "Etc/UTC") {
+                    return Err(Error::InvalidInput {
+                        message: format!(
+                            "DateTime filter bindings must be in UTC, got {}",
+                            time_zone
+                        ),
+                    });
+                }
+                format!(
+                    "timestamp '{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}'",
+                    year, month, day, hour, minute, second, microsecond
+                )
+            }
+            FilterValue::List(values) => {
+                if values.is_empty() {
+                    return Err(Error::InvalidInput {
+                        message: "list filter bindings must not be empty".to_string(),
+                    });
+                }
+                let rendered: Result<Vec<String>> = values
+                    .iter()
+                    .map(|value| match value {
+                        FilterValue::List(_) => Err(Error::InvalidInput {
+                            message: "list filter bindings must not be nested".to_string(),
+                        }),
+                        value => value.to_sql(),
+                    })
+                    .collect();
+                format!("({})", rendered?.join(", "))
+            }
+        };
+        Ok(sql)
+    }
+}
+
+impl TryFrom<Filter> for String {
+    type Error = Error;
+
+    fn try_from(value: Filter) -> Result<Self> {
+        match value {
+            Filter::Sql(sql) => Ok(sql),
+            Filter::Parameterized(filter) => filter.bind(),
+        }
+    }
+}
+
+impl ParameterizedFilter {
+    // Replaces placeholders outside of string literals, quoted identifiers and comments
+    fn bind(self) -> Result<String> {
+        let mut sql = String::with_capacity(self.sql.len());
+        let mut positional = self.params.iter();
+        let mut used_positional = false;
+        let mut used_named: Vec<String> = vec![];
+        let mut quote: Option<char> = None;
+        let mut chars = self.sql.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if let Some(q) = quote {
+                if c == q {
+                    quote = None;
+                }
+                sql.push(c);
+                continue;
+            }
+
+            match c {
+                '\'' | '"' | '`' => {
+                    quote = Some(c);
+                    sql.push(c);
+                }
+                '-' if chars.peek() == Some(&'-') => {
+                    sql.push(c);
+                    for next in chars.by_ref() {
+                        sql.push(next);
+                        if next == '\n' {
+                            break;
+                        }
+                    }
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    sql.push_str("/*");
+                    let mut previous = None;
+                    for next in chars.by_ref() {
+                        sql.push(next);
+                        if previous == Some('*') && next == '/' {
+                            break;
+                        }
+                        previous = Some(next);
+                    }
+                }
+                '?' => {
+                    used_positional = true;
+                    let value = positional.next().ok_or_else(|| Error::InvalidInput {
+                        message: format!(
+                            "filter has more placeholders than the {} bindings given",
+                            self.params.len()
+                        ),
+                    })?;
+                    sql.push_str(&value.to_sql()?);
+                }
+                '$' if chars
+                    .peek()
+                    .is_some_and(|next| next.is_alphanumeric() || *next == '_') =>
+                {
+                    let mut name = String::new();
+                    while let Some(next) = chars.peek() {
+                        if !(next.is_alphanumeric() || *next == '_') {
+                            break;
+                        }
+                        name.push(*next);
+                        chars.next();
+                    }
+                    let value =
+                        self.named_params
+                            .get(&name)
+                            .ok_or_else(|| Error::InvalidInput {
+                                message: format!("no binding given for ${}", name),
+                            })?;
+                    sql.push_str(&value.to_sql()?);
+                    used_named.push(name);
+                }
+                _ => sql.push(c),
+            }
+        }
+
+        if used_positional && !used_named.is_empty() {
+            return Err(Error::InvalidInput {
+                message: "filters cannot mix ? and $name placeholders".to_string(),
+            });
+        }
+        if positional.next().is_some() {
+            return Err(Error::InvalidInput {
+                message: format!(
+                    "filter has fewer placeholders than the {} bindings given",
+                    self.params.len()
+                ),
+            });
+        }
+        if let Some(unused) = self
+            .named_params
+            .keys()
+            .find(|name| !used_named.contains(name))
+        {
+            return Err(Error::InvalidInput {
+                message: format!("binding ${} is not used by the filter", unused),
+            });
+        }
+
+        Ok(sql)
+    }
+}
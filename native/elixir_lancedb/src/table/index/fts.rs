@@ -1,7 +1,32 @@
 use lancedb::index::scalar::{
     FtsIndexBuilder as LanceFtsIndexBuilder, TokenizerConfig as LanceTokenizerConfig,
 };
-use rustler::NifStruct;
+use rustler::{NifMap, NifStruct, NifUnitEnum};
+use tantivy::tokenizer::{TextAnalyzer, Token, TokenStream};
+
+use crate::error::{Error, Result};
+
+// Languages tantivy ships stemmers and stop word lists for
+const LANGUAGES: [&str; 18] = [
+    "arabic",
+    "danish",
+    "dutch",
+    "english",
+    "finnish",
+    "french",
+    "german",
+    "greek",
+    "hungarian",
+    "italian",
+    "norwegian",
+    "portuguese",
+    "romanian",
+    "russian",
+    "spanish",
+    "swedish",
+    "tamil",
+    "turkish",
+];
 
 #[derive(Debug, NifStruct)]
 #[module = "ElixirLanceDB.Native.Table.Index.FTS"]
@@ -10,39 +35,153 @@ pub struct FtsIndexBuilderConfig {
     pub tokenizer_configs: TokenizerConfig,
 }
 
-impl Into<LanceFtsIndexBuilder> for FtsIndexBuilderConfig {
-    fn into(self) -> LanceFtsIndexBuilder {
-        LanceFtsIndexBuilder {
-            with_position: self.with_position,
-            tokenizer_configs: self.tokenizer_configs.into(),
+impl TryFrom<FtsIndexBuilderConfig> for LanceFtsIndexBuilder {
+    type Error = Error;
+
+    fn try_from(value: FtsIndexBuilderConfig) -> Result<Self> {
+        Ok(LanceFtsIndexBuilder {
+            with_position: value.with_position,
+            tokenizer_configs: value.tokenizer_configs.try_into()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, NifUnitEnum)]
+pub enum BaseTokenizer {
+    Simple,
+    Whitespace,
+    Raw,
+    Ngram,
+}
+
+impl BaseTokenizer {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BaseTokenizer::Simple => "simple",
+            BaseTokenizer::Whitespace => "whitespace",
+            BaseTokenizer::Raw => "raw",
+            BaseTokenizer::Ngram => "ngram",
         }
     }
 }
 
-#[derive(Debug, NifStruct)]
+#[derive(Debug, Clone, NifStruct)]
 #[module = "ElixirLanceDB.Native.Table.Index.FTS.TokenizerConfig"]
 pub struct TokenizerConfig {
-    pub base_tokenizer: String,
+    pub base_tokenizer: BaseTokenizer,
     pub language: String,
     pub max_token_length: Option<usize>,
     pub lower_case: bool,
     pub stem: bool,
     pub remove_stop_words: bool,
+    pub stop_words: Option<Vec<String>>,
     pub ascii_folding: bool,
+    pub min_ngram_length: u32,
+    pub max_ngram_length: u32,
+    pub prefix_only: bool,
 }
 
-impl Into<LanceTokenizerConfig> for TokenizerConfig {
-    fn into(self) -> LanceTokenizerConfig {
-        let mut cfg = LanceTokenizerConfig::default().base_tokenizer(self.base_tokenizer);
-        cfg = cfg.clone().language(&self.language).unwrap_or(cfg);
+impl TryFrom<TokenizerConfig> for LanceTokenizerConfig {
+    type Error = Error;
+
+    fn try_from(value: TokenizerConfig) -> Result<Self> {
+        let language = value.language.to_lowercase();
+        if !LANGUAGES.contains(&language.as_str()) {
+            return Err(Error::InvalidInput {
+                message: format!(
+                    "unsupported tokenizer language {}, expected one of: {}",
+                    value.language,
+                    LANGUAGES.join(", ")
+                ),
+            });
+        }
 
-        if let Some(max_token_length) = self.max_token_length {
+        // tantivy deserializes languages by their capitalized names
+        let language = language[..1].to_uppercase() + &language[1..];
+        let mut cfg = LanceTokenizerConfig::default()
+            .base_tokenizer(value.base_tokenizer.as_str().to_string())
+            .language(&language)
+            .map_err(|e| Error::InvalidInput {
+                message: e.to_string(),
+            })?;
+
+        if let Some(max_token_length) = value.max_token_length {
             cfg = cfg.max_token_length(Some(max_token_length))
         };
 
-        cfg.lower_case(self.lower_case)
-            .stem(self.stem)
-            .remove_stop_words(self.remove_stop_words)
-            .ascii_folding(self.ascii_folding)
+        if let BaseTokenizer::Ngram = value.base_tokenizer {
+            if value.min_ngram_length == 0 || value.min_ngram_length > value.max_ngram_length {
+                return Err(Error::InvalidInput {
+                    message: format!(
+                        "invalid ngram lengths: min {} must be at least 1 and no greater than max {}",
+                        value.min_ngram_length, value.max_ngram_length
+                    ),
+                });
+            }
+
+            cfg = cfg
+                .ngram_min_length(value.min_ngram_length)
+                .ngram_max_length(value.max_ngram_length)
+                .ngram_prefix_only(value.prefix_only);
+        }
+
+        if value.stop_words.is_some() {
+            if !value.remove_stop_words {
+                return Err(Error::InvalidInput {
+                    message: "custom stop words require remove_stop_words to be enabled"
+                        .to_string(),
+                });
+            }
+
+            cfg = cfg.custom_stop_words(value.stop_words);
+        }
+
+        Ok(cfg
+            .lower_case(value.lower_case)
+            .stem(value.stem)
+            .remove_stop_words(value.remove_stop_words)
+            .ascii_folding(value.ascii_folding))
+    }
+}
+
+impl TokenizerConfig {
+    // Builds the same tantivy analyzer Lance uses when indexing with this config
+    pub fn analyzer(&self) -> Result<TextAnalyzer> {
+        let cfg: LanceTokenizerConfig = self.clone().try_into()?;
+        cfg.build().map_err(|e| Error::Lance {
+            message: e.to_string(),
+        })
     }
 }
+
+pub fn tokenize(analyzer: &mut TextAnalyzer, text: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = vec![];
+    analyzer
+        .token_stream(text)
+        .process(&mut |token: &Token| tokens.push(token.clone()));
+    tokens
+}
+
+#[derive(NifMap)]
+pub struct PreviewToken {
+    pub text: String,
+    pub position: usize,
+    pub offset_from: usize,
+    pub offset_to: usize,
+}
+
+#[rustler::nif]
+pub fn preview_tokenization(config: TokenizerConfig, text: String) -> Result<Vec<PreviewToken>> {
+    let mut analyzer = config.analyzer()?;
+    let tokens = tokenize(&mut analyzer, &text)
+        .into_iter()
+        .map(|token| PreviewToken {
+            text: token.text,
+            position: token.position,
+            offset_from: token.offset_from,
+            offset_to: token.offset_to,
+        })
+        .collect();
+
+    Ok(tokens)
+}
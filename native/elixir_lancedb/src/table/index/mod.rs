@@ -1,4 +1,4 @@
-mod fts;
+pub mod fts;
 mod ivf_pq;
 
 use fts::FtsIndexBuilderConfig;
@@ -33,16 +33,20 @@ pub enum IndexConfig {
     // IvfHnswSq(IvfHnswSqIndexBuilderConfig),
 }
 
-impl Into<Index> for IndexConfig {
-    fn into(self) -> Index {
-        match self {
+impl TryFrom<IndexConfig> for Index {
+    type Error = Error;
+
+    fn try_from(value: IndexConfig) -> Result<Self> {
+        let index = match value {
             IndexConfig::Auto => Index::Auto,
             IndexConfig::BTree => Index::BTree(BTreeIndexBuilder {}),
             IndexConfig::Bitmap => Index::Bitmap(BitmapIndexBuilder {}),
             IndexConfig::LabelList => Index::LabelList(LabelListIndexBuilder {}),
             IndexConfig::IvfPq(cfg) => Index::IvfPq(cfg.into()),
-            IndexConfig::FullTextSearch(cfg) => Index::FTS(cfg.into()),
-        }
+            IndexConfig::FullTextSearch(cfg) => Index::FTS(cfg.try_into()?),
+        };
+
+        Ok(index)
     }
 }
 
@@ -166,7 +170,7 @@ pub fn create_index(
 ) -> Result<()> {
     let table = table_conn(table)?;
     get_runtime().block_on(async {
-        let idx_builder = table.create_index(&fields, index_cfg.into());
+        let idx_builder = table.create_index(&fields, index_cfg.try_into()?);
         idx_builder.execute().await?;
         Ok::<(), Error>(())
     })?;
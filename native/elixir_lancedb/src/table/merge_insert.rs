@@ -1,5 +1,34 @@
-use arrow_array::{RecordBatch, RecordBatchIterator};
-use rustler::{Decoder, NifResult, ResourceArc, Term};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use arrow_array::{Array, ArrayRef, BooleanArray, RecordBatch, RecordBatchIterator, UInt32Array};
+use arrow_cast::{
+    cast,
+    display::{ArrayFormatter, FormatOptions},
+};
+use arrow_ord::{
+    ord::make_comparator,
+    sort::{lexsort_to_indices, SortColumn, SortOptions},
+};
+use arrow_row::{Row, RowConverter, SortField};
+use arrow_schema::SchemaRef;
+use arrow_select::{
+    concat::concat_batches,
+    filter::filter_record_batch,
+    take::{take, take_record_batch},
+};
+use futures::{future, TryStreamExt};
+use lance::{
+    dataset::{
+        MergeInsertBuilder, MergeStats as LanceMergeStats, WhenMatched, WhenNotMatched,
+        WhenNotMatchedBySource,
+    },
+    Dataset,
+};
+use lancedb::Table;
+use rustler::{Decoder, NifMap, NifResult, NifUnitEnum, ResourceArc, Term};
 
 use crate::{
     atoms,
@@ -8,53 +37,531 @@ use crate::{
     rustler_arrow::term_to_arrow::to_arrow,
 };
 
-use super::{table_conn, TableResource};
+use super::{filter::Filter, not_native, table_conn, update::ColumnOperation, TableResource};
 
 pub struct MergeInsertConfig {
     on: Vec<String>,
     when_matched_update_all: bool,
-    when_matched_update_all_filt: Option<String>,
+    when_matched_update_all_filt: Option<Filter>,
+    when_matched_update_columns: Vec<String>,
+    when_matched_update_columns_filt: Option<Filter>,
     when_not_matched_insert_all: bool,
     when_not_matched_by_source_delete: bool,
-    when_not_matched_by_source_delete_filt: Option<String>,
+    when_not_matched_by_source_delete_filt: Option<Filter>,
+    when_not_matched_by_source_update: Vec<ColumnOperation>,
+    when_not_matched_by_source_update_filt: Option<Filter>,
+    duplicate_keys: DuplicateKeys,
 }
 
+// What to do when several source rows share the same `on` key
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum DuplicateKeys {
+    Reject,
+    LastWriteWins,
+}
+
+#[derive(NifMap, Debug, Default)]
+pub struct MergeResult {
+    pub num_inserted_rows: u64,
+    pub num_updated_rows: u64,
+    pub num_deleted_rows: u64,
+    pub version: u64,
+}
+
+// Plain clauses go through lancedb, so they also run on remote tables. Column and
+// by-source updates run on the Lance dataset directly, as lancedb doesn't support
+// them, and still commit everything as a single Lance merge.
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn merge_insert(
     table: ResourceArc<TableResource>,
     input: Term,
     config: MergeInsertConfig,
-) -> Result<()> {
+) -> Result<MergeResult> {
     let table = table_conn(table)?;
+    config.validate()?;
 
     get_runtime().block_on(async {
         let schema = table.schema().await?;
         let columns = to_arrow(input, (*schema).clone())?;
-        let ons: Vec<&str> = config.on.iter().map(|s| s.as_str()).collect();
-        let mut builder = table.merge_insert(&ons);
-        if config.when_matched_update_all {
-            builder = builder
-                .when_matched_update_all(config.when_matched_update_all_filt)
-                .clone();
+        let batch = RecordBatch::try_new(schema.clone(), columns).map_err(|e| Error::from(e))?;
+        let batch = dedupe(
+            &config.on,
+            batch,
+            config.duplicate_keys,
+            "merge_insert input",
+        )?;
+
+        if config.when_matched_update_columns.is_empty()
+            && config.when_not_matched_by_source_update.is_empty()
+        {
+            return lancedb_merge(&table, &config, batch).await;
         }
 
-        if config.when_not_matched_insert_all {
-            builder = builder.when_not_matched_insert_all().clone();
+        let wrapper = table.dataset().ok_or_else(not_native)?;
+        // held across reading the target and the merge so both see the same version
+        let mut latest = wrapper.get_mut().await?;
+        let dataset = Arc::new(latest.clone());
+        let mut builder = MergeInsertBuilder::try_new(dataset.clone(), config.on.clone())?;
+
+        let source = if !config.when_matched_update_columns.is_empty()
+            && !config.when_not_matched_insert_all
+            && !config.when_not_matched_by_source_delete
+            && config.when_not_matched_by_source_update.is_empty()
+        {
+            // matched rows get only the given columns, by merging the source projected to them
+            let when_matched = match bind(config.when_matched_update_columns_filt.clone())? {
+                Some(filt) => WhenMatched::update_if(&dataset, &filt)?,
+                None => WhenMatched::UpdateAll,
+            };
+            builder
+                .when_matched(when_matched)
+                .when_not_matched(WhenNotMatched::DoNothing);
+            let projection = config
+                .on
+                .iter()
+                .chain(config.when_matched_update_columns.iter())
+                .map(|name| schema.index_of(name))
+                .collect::<std::result::Result<Vec<usize>, _>>()?;
+            batch.project(&projection)?
+        } else {
+            // Every source row is a full row, so the merge can insert and delete as well.
+            // Matched rows take the unlisted columns from the table, and the target rows
+            // updated by source are added with their new values, so updating every
+            // matched row changes only what the clauses ask for.
+            let mut parts = vec![];
+            if config.when_matched_update_all {
+                parts.push(batch.clone());
+            } else if !config.when_matched_update_columns.is_empty()
+                || config.when_not_matched_insert_all
+            {
+                let (unmatched, matched) = split_matched(&dataset, &config, &batch).await?;
+                parts.push(unmatched);
+                if !config.when_matched_update_columns.is_empty() {
+                    parts.push(matched);
+                }
+            }
+            if !config.when_not_matched_by_source_update.is_empty() {
+                parts.push(by_source_updates(&dataset, &config, &batch).await?);
+            }
+            let when_not_matched = match config.when_not_matched_insert_all {
+                true => WhenNotMatched::InsertAll,
+                false => WhenNotMatched::DoNothing,
+            };
+            let when_not_matched_by_source = match (
+                config.when_not_matched_by_source_delete,
+                bind(config.when_not_matched_by_source_delete_filt.clone())?,
+            ) {
+                (false, _) => WhenNotMatchedBySource::Keep,
+                (true, None) => WhenNotMatchedBySource::Delete,
+                (true, Some(filt)) => WhenNotMatchedBySource::delete_if(&dataset, &filt)?,
+            };
+            builder
+                .when_matched(WhenMatched::UpdateAll)
+                .when_not_matched(when_not_matched)
+                .when_not_matched_by_source(when_not_matched_by_source);
+            concat_batches(&schema, &parts)?
+        };
+
+        let (dataset, stats) = execute(&mut builder, source).await?;
+        *latest = dataset.as_ref().clone();
+
+        Ok::<MergeResult, Error>(MergeResult {
+            num_inserted_rows: stats.num_inserted_rows,
+            num_updated_rows: stats.num_updated_rows,
+            num_deleted_rows: stats.num_deleted_rows,
+            version: dataset.version().version,
+        })
+    })
+}
+
+async fn lancedb_merge(
+    table: &Table,
+    config: &MergeInsertConfig,
+    batch: RecordBatch,
+) -> Result<MergeResult> {
+    let on: Vec<&str> = config.on.iter().map(String::as_str).collect();
+    let mut builder = table.merge_insert(&on);
+    if config.when_matched_update_all {
+        builder.when_matched_update_all(bind(config.when_matched_update_all_filt.clone())?);
+    }
+    if config.when_not_matched_insert_all {
+        builder.when_not_matched_insert_all();
+    }
+    if config.when_not_matched_by_source_delete {
+        builder.when_not_matched_by_source_delete(bind(
+            config.when_not_matched_by_source_delete_filt.clone(),
+        )?);
+    }
+
+    let schema = batch.schema();
+    let reader = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+    let result = builder.execute(Box::new(reader)).await?;
+    Ok(MergeResult {
+        num_inserted_rows: result.num_inserted_rows,
+        num_updated_rows: result.num_updated_rows,
+        num_deleted_rows: result.num_deleted_rows,
+        version: result.version,
+    })
+}
+
+async fn execute(
+    builder: &mut MergeInsertBuilder,
+    batch: RecordBatch,
+) -> Result<(Arc<Dataset>, LanceMergeStats)> {
+    let schema = batch.schema();
+    let reader = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+    Ok(builder.try_build()?.execute_reader(reader).await?)
+}
+
+// Splits the source into its rows whose key is not in the table, and full rows for
+// those whose key is, with the keys and `when_matched_update_columns` from the source
+// and every other column as the table has it
+async fn split_matched(
+    dataset: &Dataset,
+    config: &MergeInsertConfig,
+    source: &RecordBatch,
+) -> Result<(RecordBatch, RecordBatch)> {
+    let schema = source.schema();
+    let keys = key_indices(&schema, &config.on)?;
+    let converter = key_converter(&schema, &keys)?;
+    let source_keys = converter.convert_columns(source.project(&keys)?.columns())?;
+    let by_key: HashMap<Row, u32> = source_keys
+        .iter()
+        .enumerate()
+        .map(|(idx, key)| (key, idx as u32))
+        .collect();
+    let in_source: HashSet<Row> = by_key.keys().copied().collect();
+
+    let mut scanner = dataset.scan();
+    scanner.filter(&not_null_keys(&config.on))?;
+    let batches: Vec<RecordBatch> = scanner
+        .try_into_stream()
+        .await?
+        .map_err(Error::from)
+        .and_then(|batch| {
+            future::ready(rows_where(
+                batch, &keys, &converter, &in_source, true, &schema,
+            ))
+        })
+        .try_collect()
+        .await?;
+    let matched = dedupe(
+        &config.on,
+        concat_batches(&schema, &batches)?,
+        DuplicateKeys::Reject,
+        "table",
+    )?;
+
+    let matched_keys = converter.convert_columns(matched.project(&keys)?.columns())?;
+    let source_rows: UInt32Array = matched_keys
+        .iter()
+        .map(|key| by_key.get(&key).copied())
+        .collect();
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            if config.on.contains(field.name())
+                || config.when_matched_update_columns.contains(field.name())
+            {
+                take(source.column(idx).as_ref(), &source_rows, None)
+            } else {
+                Ok(matched.column(idx).clone())
+            }
+        })
+        .collect::<std::result::Result<Vec<ArrayRef>, _>>()?;
+    let matched = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let is_matched: HashSet<u32> = source_rows.values().iter().copied().collect();
+    let unmatched: BooleanArray = (0..source.num_rows() as u32)
+        .map(|idx| Some(!is_matched.contains(&idx)))
+        .collect();
+    Ok((filter_record_batch(source, &unmatched)?, matched))
+}
+
+// The target rows whose key is absent from the source, with their new values.
+// Rows with a null key can't be matched by a merge, so they are left as they are.
+async fn by_source_updates(
+    dataset: &Dataset,
+    config: &MergeInsertConfig,
+    source: &RecordBatch,
+) -> Result<RecordBatch> {
+    let schema = source.schema();
+    for op in &config.when_not_matched_by_source_update {
+        schema.index_of(&op.column)?;
+    }
+    let transforms: Vec<(String, String)> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let expression = match config
+                .when_not_matched_by_source_update
+                .iter()
+                .find(|op| &op.column == field.name())
+            {
+                Some(op) => op.operation.clone(),
+                None => format!("`{}`", field.name()),
+            };
+            (field.name().clone(), expression)
+        })
+        .collect();
+
+    let mut filter = not_null_keys(&config.on);
+    if let Some(filt) = bind(config.when_not_matched_by_source_update_filt.clone())? {
+        filter = format!("{} AND ({})", filter, filt);
+    }
+
+    let keys = key_indices(&schema, &config.on)?;
+    let converter = key_converter(&schema, &keys)?;
+    let source_keys = converter.convert_columns(source.project(&keys)?.columns())?;
+    let in_source: HashSet<Row> = source_keys.iter().collect();
+
+    let mut scanner = dataset.scan();
+    scanner
+        .project_with_transform(&transforms)?
+        .filter(&filter)?;
+    let batches: Vec<RecordBatch> = scanner
+        .try_into_stream()
+        .await?
+        .map_err(Error::from)
+        .and_then(|batch| {
+            future::ready(rows_where(
+                batch, &keys, &converter, &in_source, false, &schema,
+            ))
+        })
+        .try_collect()
+        .await?;
+
+    let updates = concat_batches(&schema, &batches)?;
+    dedupe(&config.on, updates, DuplicateKeys::Reject, "table")
+}
+
+// The rows of a target batch whose key is among the source's, or with `in_source` false, isn't
+fn rows_where(
+    batch: RecordBatch,
+    keys: &[usize],
+    converter: &RowConverter,
+    source_keys: &HashSet<Row>,
+    in_source: bool,
+    schema: &SchemaRef,
+) -> Result<RecordBatch> {
+    let kept: BooleanArray = converter
+        .convert_columns(batch.project(keys)?.columns())?
+        .iter()
+        .map(|key| Some(source_keys.contains(&key) == in_source))
+        .collect();
+    let batch = filter_record_batch(&batch, &kept)?;
+    // updated values take the types of the columns they replace
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| cast(column, field.data_type()))
+        .collect::<std::result::Result<Vec<ArrayRef>, _>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn key_indices(schema: &SchemaRef, on: &[String]) -> Result<Vec<usize>> {
+    Ok(on
+        .iter()
+        .map(|name| schema.index_of(name))
+        .collect::<std::result::Result<Vec<usize>, _>>()?)
+}
+
+fn key_converter(schema: &SchemaRef, keys: &[usize]) -> Result<RowConverter> {
+    let fields = keys
+        .iter()
+        .map(|idx| SortField::new(schema.field(*idx).data_type().clone()))
+        .collect();
+    Ok(RowConverter::new(fields)?)
+}
+
+fn not_null_keys(on: &[String]) -> String {
+    on.iter()
+        .map(|name| format!("`{}` IS NOT NULL", name))
+        .collect::<Vec<String>>()
+        .join(" AND ")
+}
+
+// Rows with a null key never match, so they are never duplicates either
+fn dedupe(
+    on: &[String],
+    batch: RecordBatch,
+    policy: DuplicateKeys,
+    what: &str,
+) -> Result<RecordBatch> {
+    let keys = on
+        .iter()
+        .map(|name| {
+            batch
+                .column_by_name(name)
+                .cloned()
+                .ok_or_else(|| Error::InvalidInput {
+                    message: format!("no column named {} in the {}", name, what),
+                })
+        })
+        .collect::<Result<Vec<ArrayRef>>>()?;
+    let sort_columns: Vec<SortColumn> = keys
+        .iter()
+        .map(|key| SortColumn {
+            values: key.clone(),
+            options: None,
+        })
+        .collect();
+    let comparators = keys
+        .iter()
+        .map(|key| make_comparator(key.as_ref(), key.as_ref(), SortOptions::default()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let same_key = |a: usize, b: usize| {
+        keys.iter()
+            .zip(&comparators)
+            .all(|(key, compare)| key.is_valid(a) && key.is_valid(b) && compare(a, b).is_eq())
+    };
+
+    let sorted = lexsort_to_indices(&sort_columns, None)?;
+    let sorted = sorted.values();
+    let mut kept: Vec<u32> = vec![];
+    let mut duplicated: Vec<usize> = vec![];
+    let mut start = 0;
+    while start < sorted.len() {
+        let mut end = start + 1;
+        while end < sorted.len() && same_key(sorted[start] as usize, sorted[end] as usize) {
+            end += 1;
+        }
+        let group = &sorted[start..end];
+        if group.len() > 1 {
+            duplicated.push(group[0] as usize);
         }
+        // the row given last in the input wins
+        kept.extend(group.iter().max());
+        start = end;
+    }
+
+    if duplicated.is_empty() {
+        return Ok(batch);
+    }
 
-        if config.when_not_matched_by_source_delete {
-            builder = builder
-                .when_not_matched_by_source_delete(config.when_not_matched_by_source_delete_filt)
-                .clone();
+    match policy {
+        DuplicateKeys::Reject => {
+            let formatters = keys
+                .iter()
+                .map(|key| ArrayFormatter::try_new(key.as_ref(), &FormatOptions::default()))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            duplicated.sort_unstable();
+            let examples: Vec<String> = duplicated
+                .iter()
+                .take(MAX_REPORTED_KEYS)
+                .map(|row| {
+                    let values: Vec<String> = formatters
+                        .iter()
+                        .map(|formatter| formatter.value(*row).to_string())
+                        .collect();
+                    format!("({})", values.join(", "))
+                })
+                .collect();
+            Err(Error::InvalidInput {
+                message: format!(
+                    "{} has {} duplicated keys on ({}): {}",
+                    what,
+                    duplicated.len(),
+                    on.join(", "),
+                    examples.join(", ")
+                ),
+            })
         }
+        DuplicateKeys::LastWriteWins => {
+            kept.sort_unstable();
+            Ok(take_record_batch(&batch, &UInt32Array::from(kept))?)
+        }
+    }
+}
 
-        let batch = RecordBatch::try_new(schema.clone(), columns).map_err(|e| Error::from(e))?;
-        let reader = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
-        builder.execute(Box::new(reader)).await?;
-        Ok::<(), Error>(())
-    })?;
+const MAX_REPORTED_KEYS: usize = 10;
 
-    Ok(())
+fn bind(filter: Option<Filter>) -> Result<Option<String>> {
+    filter.map(String::try_from).transpose()
+}
+
+impl MergeInsertConfig {
+    fn validate(&self) -> Result<()> {
+        if !self.when_matched_update_all
+            && self.when_matched_update_columns.is_empty()
+            && !self.when_not_matched_insert_all
+            && !self.when_not_matched_by_source_delete
+            && self.when_not_matched_by_source_update.is_empty()
+        {
+            return Err(Error::InvalidInput {
+                message: "merge_insert is not configured to change the data in any way".to_string(),
+            });
+        }
+        if self.when_matched_update_all && !self.when_matched_update_columns.is_empty() {
+            return Err(Error::InvalidInput {
+                message:
+                    "when_matched_update_all and when_matched_update_columns cannot be combined"
+                        .to_string(),
+            });
+        }
+        if self.when_not_matched_by_source_delete
+            && !self.when_not_matched_by_source_update.is_empty()
+        {
+            return Err(Error::InvalidInput {
+                message: "when_not_matched_by_source_delete and when_not_matched_by_source_update cannot be combined"
+                    .to_string(),
+            });
+        }
+        // with other clauses every matched row is updated, including the target rows
+        // updated by source, so a matched-row filter only applies to a clause run alone
+        let other_clauses = self.when_not_matched_insert_all
+            || self.when_not_matched_by_source_delete
+            || !self.when_not_matched_by_source_update.is_empty();
+        if self.when_matched_update_columns_filt.is_some() && other_clauses {
+            return Err(Error::InvalidInput {
+                message: "when_matched_update_columns_filt cannot be combined with other clauses"
+                    .to_string(),
+            });
+        }
+        if self.when_matched_update_all_filt.is_some()
+            && self.when_matched_update_all
+            && !self.when_not_matched_by_source_update.is_empty()
+        {
+            return Err(Error::InvalidInput {
+                message:
+                    "when_matched_update_all_filt cannot be combined with when_not_matched_by_source_update"
+                        .to_string(),
+            });
+        }
+        if let Some(op) = self
+            .when_not_matched_by_source_update
+            .iter()
+            .find(|op| self.on.contains(&op.column))
+        {
+            return Err(Error::InvalidInput {
+                message: format!(
+                    "when_not_matched_by_source_update cannot change the key column {}",
+                    op.column
+                ),
+            });
+        }
+        if self.when_matched_update_columns_filt.is_some()
+            && self.when_matched_update_columns.is_empty()
+        {
+            return Err(Error::InvalidInput {
+                message: "when_matched_update_columns_filt needs when_matched_update_columns"
+                    .to_string(),
+            });
+        }
+        if self.when_not_matched_by_source_update_filt.is_some()
+            && self.when_not_matched_by_source_update.is_empty()
+        {
+            return Err(Error::InvalidInput {
+                message:
+                    "when_not_matched_by_source_update_filt needs when_not_matched_by_source_update"
+                        .to_string(),
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Decoder<'_> for MergeInsertConfig {
@@ -65,10 +572,25 @@ impl Decoder<'_> for MergeInsertConfig {
             .map_get(atoms::when_matched_update_all())
             .and_then(|s| s.decode())?;
 
-        let when_matched_update_all_filt: Option<String> = term
+        let when_matched_update_all_filt: Option<Filter> = term
             .map_get(atoms::when_matched_update_all_filt())
             .ok()
-            .and_then(|s| s.decode().ok());
+            // a filter that fails to decode must not be silently dropped
+            .map(|s| s.decode::<Option<Filter>>())
+            .transpose()?
+            .flatten();
+
+        let when_matched_update_columns: Vec<String> = term
+            .map_get(atoms::when_matched_update_columns())
+            .and_then(|s| s.decode())?;
+
+        let when_matched_update_columns_filt: Option<Filter> = term
+            .map_get(atoms::when_matched_update_columns_filt())
+            .ok()
+            // a filter that fails to decode must not be silently dropped
+            .map(|s| s.decode::<Option<Filter>>())
+            .transpose()?
+            .flatten();
 
         let when_not_matched_insert_all: bool = term
             .map_get(atoms::when_not_matched_insert_all())
@@ -78,18 +600,42 @@ impl Decoder<'_> for MergeInsertConfig {
             .map_get(atoms::when_not_matched_by_source_delete())
             .and_then(|s| s.decode())?;
 
-        let when_not_matched_by_source_delete_filt: Option<String> = term
+        let when_not_matched_by_source_delete_filt: Option<Filter> = term
             .map_get(atoms::when_not_matched_by_source_delete_filt())
             .ok()
-            .and_then(|s| s.decode().ok());
+            // a filter that fails to decode must not be silently dropped
+            .map(|s| s.decode::<Option<Filter>>())
+            .transpose()?
+            .flatten();
+
+        let when_not_matched_by_source_update: Vec<ColumnOperation> = term
+            .map_get(atoms::when_not_matched_by_source_update())
+            .and_then(|s| s.decode())?;
+
+        let when_not_matched_by_source_update_filt: Option<Filter> = term
+            .map_get(atoms::when_not_matched_by_source_update_filt())
+            .ok()
+            // a filter that fails to decode must not be silently dropped
+            .map(|s| s.decode::<Option<Filter>>())
+            .transpose()?
+            .flatten();
+
+        let duplicate_keys: DuplicateKeys = term
+            .map_get(atoms::duplicate_keys())
+            .and_then(|s| s.decode())?;
 
         Ok(MergeInsertConfig {
             on,
             when_matched_update_all,
             when_matched_update_all_filt,
+            when_matched_update_columns,
+            when_matched_update_columns_filt,
             when_not_matched_insert_all,
             when_not_matched_by_source_delete,
             when_not_matched_by_source_delete_filt,
+            when_not_matched_by_source_update,
+            when_not_matched_by_source_update_filt,
+            duplicate_keys,
         })
     }
 }
@@ -1,15 +1,19 @@
 mod add;
 mod add_columns;
 mod alter_columns;
+mod batch_udf;
 mod delete;
+mod filter;
 mod index;
 mod merge_insert;
 mod optimize;
 mod query;
+mod returning;
 mod update;
 
 use std::sync::{Arc, Mutex};
 
+use filter::Filter;
 use lancedb::Table;
 use rustler::{resource_impl, Resource, ResourceArc};
 
@@ -34,8 +38,9 @@ pub fn close_table(table: ResourceArc<TableResource>) -> Result<()> {
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn count_rows(table: ResourceArc<TableResource>, filter: String) -> Result<usize> {
+pub fn count_rows(table: ResourceArc<TableResource>, filter: Filter) -> Result<usize> {
     let table = table_conn(table)?;
+    let filter: String = filter.try_into()?;
     let filter = match filter.as_str() {
         "" => None,
         _ => Some(filter),
@@ -51,7 +56,7 @@ pub fn count_rows(table: ResourceArc<TableResource>, filter: String) -> Result<u
 pub fn schema(table: ResourceArc<TableResource>) -> Result<Schema> {
     let table = table_conn(table)?;
     let schema = get_runtime().block_on(async {
-        let s: Schema = table.schema().await?.into();
+        let s: Schema = table.schema().await?.try_into()?;
         Ok::<Schema, Error>(s)
     })?;
 
@@ -67,6 +72,13 @@ pub fn drop_columns(table: ResourceArc<TableResource>, columns: Vec<&str>) -> Re
     })
 }
 
+// lancedb only exposes the Lance dataset of tables opened from a local path
+fn not_native() -> Error {
+    Error::LanceNotSupported {
+        message: "this operation is only supported on local tables".to_string(),
+    }
+}
+
 fn table_conn(table: ResourceArc<TableResource>) -> Result<Table> {
     let result;
     {
@@ -0,0 +1,181 @@
+use std::{fmt, sync::Mutex, time::Duration};
+
+use arrow_array::{Float32Array, RecordBatch};
+use async_trait::async_trait;
+use lancedb::{rerankers::Reranker, Error as LanceError};
+use rustler::{resource_impl, Encoder, LocalPid, NifStruct, OwnedEnv, Resource, ResourceArc};
+use tokio::sync::oneshot;
+
+use crate::{
+    atoms,
+    error::{Error, Result},
+    rustler_arrow::term_from_arrow::from_arrow,
+};
+
+use super::hybrid::{sort_by_relevance, ROW_ID_COLUMN};
+
+// Handed to Elixir with each batch of candidates; replying through it wakes the waiting search
+pub struct RerankReplyResource(
+    Mutex<Option<oneshot::Sender<std::result::Result<Vec<f32>, String>>>>,
+);
+
+#[resource_impl]
+impl Resource for RerankReplyResource {}
+
+#[rustler::nif]
+pub fn rerank_reply(reply: ResourceArc<RerankReplyResource>, scores: Vec<f32>) -> Result<()> {
+    take_sender(&reply)?
+        .send(Ok(scores))
+        .map_err(|_| Error::InvalidInput {
+            message: "this rerank request has already timed out".to_string(),
+        })
+}
+
+// Fails the search the request belongs to, e.g. when the rerank function raised
+#[rustler::nif]
+pub fn rerank_reply_error(reply: ResourceArc<RerankReplyResource>, message: String) -> Result<()> {
+    take_sender(&reply)?
+        .send(Err(format!("the rerank function failed: {}", message)))
+        .map_err(|_| Error::InvalidInput {
+            message: "this rerank request has already timed out".to_string(),
+        })
+}
+
+fn take_sender(
+    reply: &RerankReplyResource,
+) -> Result<oneshot::Sender<std::result::Result<Vec<f32>, String>>> {
+    reply
+        .0
+        .lock()
+        .map_err(|_| Error::Other {
+            message: "rerank reply lock poisoned".to_string(),
+        })?
+        .take()
+        .ok_or_else(|| Error::InvalidInput {
+            message: "this rerank request has already been answered".to_string(),
+        })
+}
+
+#[derive(NifStruct, Clone)]
+#[module = "ElixirLanceDB.Native.Table.Reranker.Callback"]
+pub struct CallbackRerankerConfig {
+    pub pid: LocalPid,
+    pub timeout: u64,
+}
+
+// Sends candidates to an Elixir process as
+// `{:elixir_lancedb_rerank, reply, query, rows}` and orders them by the
+// scores it replies with, one per row. The query is the full text search
+// text, and empty for a vector search, which has none.
+pub struct CallbackReranker {
+    pid: LocalPid,
+    timeout: Duration,
+}
+
+impl From<CallbackRerankerConfig> for CallbackReranker {
+    fn from(value: CallbackRerankerConfig) -> Self {
+        CallbackReranker {
+            pid: value.pid,
+            timeout: Duration::from_millis(value.timeout),
+        }
+    }
+}
+
+impl fmt::Debug for CallbackReranker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackReranker")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl CallbackReranker {
+    async fn score(&self, query: &str, candidates: RecordBatch) -> lancedb::Result<RecordBatch> {
+        // row ids are only needed for merging and have no term representation
+        let mut rows = candidates.clone();
+        if let Ok(index) = rows.schema().index_of(ROW_ID_COLUMN) {
+            rows.remove_column(index);
+        }
+        let rows = from_arrow(vec![rows]).map_err(|_| LanceError::Runtime {
+            message: "failed converting rerank candidates".to_string(),
+        })?;
+
+        let (sender, receiver) = oneshot::channel();
+        let reply = ResourceArc::new(RerankReplyResource(Mutex::new(Some(sender))));
+        let pid = self.pid;
+        let query = query.to_string();
+
+        // OwnedEnv can't send from a scheduler thread, which is where this search runs
+        tokio::task::spawn_blocking(move || {
+            OwnedEnv::new().send_and_clear(&pid, |env| {
+                (atoms::elixir_lancedb_rerank(), reply, query, rows).encode(env)
+            })
+        })
+        .await
+        .map_err(|e| LanceError::Runtime {
+            message: e.to_string(),
+        })?
+        .map_err(|_| LanceError::Runtime {
+            message: "the rerank process is not alive".to_string(),
+        })?;
+
+        let scores = match tokio::time::timeout(self.timeout, receiver).await {
+            Err(_) => {
+                return Err(LanceError::Timeout {
+                    message: format!(
+                        "the rerank process did not reply within {}ms",
+                        self.timeout.as_millis()
+                    ),
+                })
+            }
+            Ok(Err(_)) => {
+                return Err(LanceError::Runtime {
+                    message: "the rerank request was dropped without a reply".to_string(),
+                })
+            }
+            Ok(Ok(Err(message))) => return Err(LanceError::Runtime { message }),
+            Ok(Ok(Ok(scores))) => scores,
+        };
+
+        if scores.len() != candidates.num_rows() {
+            return Err(LanceError::InvalidInput {
+                message: format!(
+                    "expected {} rerank scores but received {}",
+                    candidates.num_rows(),
+                    scores.len()
+                ),
+            });
+        }
+
+        sort_by_relevance(candidates, Float32Array::from(scores))
+    }
+}
+
+#[async_trait]
+impl Reranker for CallbackReranker {
+    async fn rerank_vector(
+        &self,
+        query: &str,
+        vector_results: RecordBatch,
+    ) -> lancedb::Result<RecordBatch> {
+        self.score(query, vector_results).await
+    }
+
+    async fn rerank_fts(
+        &self,
+        query: &str,
+        fts_results: RecordBatch,
+    ) -> lancedb::Result<RecordBatch> {
+        self.score(query, fts_results).await
+    }
+
+    async fn rerank_hybrid(
+        &self,
+        query: &str,
+        vector_results: RecordBatch,
+        fts_results: RecordBatch,
+    ) -> lancedb::Result<RecordBatch> {
+        let merged = self.merge_results(vector_results, fts_results)?;
+        self.score(query, merged).await
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use arrow_array::{cast::AsArray, types::UInt64Type, RecordBatch};
+use futures::TryStreamExt;
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use rustler::{NifStruct, ResourceArc};
+
+use crate::{
+    error::{Error, Result},
+    runtime::get_runtime,
+    rustler_arrow::term_from_arrow::{from_arrow, ReturnableTerm},
+    table::{filter::Filter, table_conn, TableResource},
+};
+
+use super::{
+    hybrid::{DEFAULT_LIMIT, ROW_ID_COLUMN},
+    vector::{QueryVector, VectorQueryRequest},
+};
+
+#[derive(NifStruct, Clone, Debug)]
+#[rustler(decode)]
+#[module = "ElixirLanceDB.Native.Table.SearchByExample"]
+pub struct SearchByExample {
+    pub column: String,
+    pub filter: Option<Filter>,
+    pub row_id: Option<u64>,
+    pub exclude_source: bool,
+}
+
+// Runs a nearest neighbour search using the vector of an existing row
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn search_by_example(
+    table: ResourceArc<TableResource>,
+    example: SearchByExample,
+    request: VectorQueryRequest,
+) -> Result<Vec<HashMap<String, ReturnableTerm>>> {
+    let filter = match (example.filter, example.row_id) {
+        (Some(filter), None) => filter.try_into()?,
+        (None, Some(row_id)) => format!("{} = {}", ROW_ID_COLUMN, row_id),
+        _ => {
+            return Err(Error::InvalidInput {
+                message: "search by example takes either a filter or a row id".to_string(),
+            })
+        }
+    };
+
+    let limit = request.base.limit.unwrap_or(DEFAULT_LIMIT);
+    let offset = request.base.offset.unwrap_or(0);
+    let with_row_id = request.base.with_row_id;
+
+    let table = table_conn(table)?;
+    let results = get_runtime().block_on(async {
+        let source: Vec<RecordBatch> = table
+            .query()
+            .only_if(filter)
+            .select(Select::Columns(vec![example.column.clone()]))
+            .with_row_id()
+            .limit(2)
+            .execute()
+            .await?
+            .try_collect()
+            .await?;
+        let (source_row_id, query_vector) = example_vector(&source, &example.column)?;
+
+        let mut request = request.clone();
+        request.column = Some(example.column);
+        request.query_vector = query_vector;
+        request.query_vectors = vec![];
+
+        // page after dropping the source row so it doesn't shift the results
+        if example.exclude_source {
+            request.base.limit = Some(offset + limit + 1);
+            request.base.offset = None;
+            request.base.with_row_id = true;
+        }
+
+        let base_query = request.base.clone().apply_to(table.query())?;
+        let postfilter = request.postfilter;
+        let mut vector_query = request.apply_to(base_query)?;
+
+        if postfilter {
+            vector_query = vector_query.postfilter();
+        }
+
+        let record_batch: Vec<RecordBatch> = vector_query.execute().await?.try_collect().await?;
+        let mut rows = from_arrow(record_batch)?;
+
+        if example.exclude_source {
+            rows = rows
+                .into_iter()
+                .filter(|row| {
+                    !matches!(row.get(ROW_ID_COLUMN), Some(ReturnableTerm::UInt64(row_id)) if *row_id == source_row_id)
+                })
+                .skip(offset)
+                .take(limit)
+                .collect();
+
+            if !with_row_id {
+                rows.iter_mut().for_each(|row| {
+                    row.remove(ROW_ID_COLUMN);
+                });
+            }
+        }
+
+        Ok::<Vec<HashMap<String, ReturnableTerm>>, Error>(rows)
+    })?;
+
+    Ok(results)
+}
+
+fn example_vector(batches: &[RecordBatch], column: &str) -> Result<(u64, QueryVector)> {
+    let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    let batch = match (num_rows, batches.iter().find(|batch| batch.num_rows() > 0)) {
+        (1, Some(batch)) => batch,
+        (0, _) => {
+            return Err(Error::InvalidInput {
+                message: "no row matched the example".to_string(),
+            })
+        }
+        _ => {
+            return Err(Error::InvalidInput {
+                message: "the example must match exactly one row".to_string(),
+            })
+        }
+    };
+
+    let row_id = batch
+        .column_by_name(ROW_ID_COLUMN)
+        .and_then(|row_ids| row_ids.as_primitive_opt::<UInt64Type>())
+        .map(|row_ids| row_ids.value(0))
+        .ok_or_else(|| Error::Other {
+            message: "row id missing from example lookup".to_string(),
+        })?;
+
+    let vector = batch
+        .column_by_name(column)
+        .and_then(|vectors| vectors.as_fixed_size_list_opt())
+        .map(|vectors| vectors.value(0))
+        .ok_or_else(|| Error::InvalidInput {
+            message: format!("{} is not a vector column", column),
+        })?;
+
+    Ok((row_id, QueryVector::Array(vector)))
+}
@@ -1,4 +1,5 @@
 use crate::{
+    atoms,
     error::{Error, Result},
     runtime::get_runtime,
     rustler_arrow::term_from_arrow::{from_arrow, ReturnableTerm},
@@ -7,20 +8,32 @@ use crate::{
 use arrow_array::RecordBatch;
 use futures::TryStreamExt;
 use lancedb::{
-    index::scalar::{FtsQuery, FullTextSearchQuery as LanceFullTextSearchQuery, MatchQuery},
+    index::scalar::{
+        BooleanQuery as LanceBooleanQuery, BoostQuery as LanceBoostQuery,
+        FtsQuery as LanceFtsQuery, FullTextSearchQuery as LanceFullTextSearchQuery,
+        MatchQuery as LanceMatchQuery, MultiMatchQuery as LanceMultiMatchQuery, Occur,
+        Operator as LanceOperator, PhraseQuery as LancePhraseQuery,
+    },
     query::{ExecutableQuery, QueryBase},
 };
-use rustler::{NifStruct, ResourceArc};
+use rustler::{
+    types::elixir_struct::make_ex_struct, Decoder, Encoder, Env, NifResult, NifStruct, NifUnitEnum,
+    ResourceArc, Term,
+};
 use std::collections::HashMap;
 
-use super::plain::QueryRequest;
+use super::{
+    fts_parser,
+    highlight::{HighlightConfig, Highlighter},
+    plain::QueryRequest,
+};
 
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn full_text_search(
     table: ResourceArc<TableResource>,
     query: QueryRequest,
 ) -> Result<Vec<HashMap<String, ReturnableTerm>>> {
-    let fts_query = match query.full_text_search {
+    let fts_query = match query.full_text_search.clone() {
         None => {
             return Err(Error::InvalidInput {
                 message: "full text search query is required".to_string(),
@@ -30,34 +43,333 @@ pub fn full_text_search(
     };
 
     let table = table_conn(table)?;
-    let result: Vec<HashMap<String, ReturnableTerm>> = get_runtime().block_on(async {
-        let query = table.query().full_text_search(fts_query.into());
+    let (mut result, mut highlighter) = get_runtime().block_on(async {
+        let highlighter = match fts_query.highlight.clone() {
+            Some(config) => Some(Highlighter::load(&table, config, &fts_query).await?),
+            None => None,
+        };
+
+        // the filter, limit, offset and projection apply just as they do to a plain query
+        let query = query.apply_to(table.query())?;
         let record_batch: Vec<RecordBatch> = query.execute().await?.try_collect().await?;
         let results = from_arrow(record_batch)?;
-        Ok::<Vec<HashMap<String, ReturnableTerm>>, Error>(results)
+        Ok::<(Vec<HashMap<String, ReturnableTerm>>, Option<Highlighter>), Error>((
+            results,
+            highlighter,
+        ))
     })?;
 
+    if let Some(highlighter) = highlighter.as_mut() {
+        result.iter_mut().for_each(|row| highlighter.highlight(row));
+    }
+
     Ok(result)
 }
 
+#[rustler::nif]
+pub fn parse_fts_query(query: String, default_columns: Vec<String>) -> Result<FtsQuery> {
+    Ok(fts_parser::parse(&query, &default_columns)?)
+}
+
 #[derive(NifStruct, Clone, Debug)]
 #[module = "ElixirLanceDB.Native.Table.FullTextSearchQueryRequest"]
 pub struct FullTextSearchQuery {
-    pub query: String,
+    pub query: FtsQuery,
     pub columns: Vec<String>,
     pub limit: Option<i64>,
     pub wand_factor: Option<f32>,
+    pub highlight: Option<HighlightConfig>,
+}
+
+impl TryFrom<FullTextSearchQuery> for LanceFullTextSearchQuery {
+    type Error = Error;
+
+    fn try_from(value: FullTextSearchQuery) -> Result<Self> {
+        let query = match value.query {
+            // A plain string is matched against every requested column
+            FtsQuery::Terms(terms) => match value.columns.len() {
+                0 => LanceFtsQuery::Match(LanceMatchQuery::new(terms)),
+                1 => LanceFtsQuery::Match(
+                    LanceMatchQuery::new(terms).with_column(value.columns.into_iter().next()),
+                ),
+                _ => LanceFtsQuery::MultiMatch(
+                    MultiMatchQuery {
+                        terms,
+                        columns: value.columns,
+                        boosts: None,
+                        operator: Operator::Or,
+                    }
+                    .try_into()?,
+                ),
+            },
+            query if value.columns.is_empty() => query.try_into()?,
+            _ => return Err(Error::InvalidInput {
+                message:
+                    "columns must be set on the query nodes of a structured full text search query"
+                        .to_string(),
+            }),
+        };
+
+        Ok(LanceFullTextSearchQuery {
+            query,
+            limit: value.limit,
+            wand_factor: value.wand_factor,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum FtsQuery {
+    Terms(String),
+    Match(MatchQuery),
+    Phrase(PhraseQuery),
+    MultiMatch(MultiMatchQuery),
+    Boolean(BooleanQuery),
+    Boost(BoostQuery),
+}
+
+#[derive(NifStruct, Clone, Debug)]
+#[module = "ElixirLanceDB.Native.Table.FtsQuery.Match"]
+pub struct MatchQuery {
+    pub terms: String,
+    pub column: Option<String>,
+    pub boost: f32,
+    pub fuzziness: Option<u32>,
+    pub max_expansions: usize,
+    pub operator: Operator,
+}
+
+#[derive(NifStruct, Clone, Debug)]
+#[module = "ElixirLanceDB.Native.Table.FtsQuery.Phrase"]
+pub struct PhraseQuery {
+    pub terms: String,
+    pub column: Option<String>,
+    pub slop: u32,
+}
+
+#[derive(NifStruct, Clone, Debug)]
+#[module = "ElixirLanceDB.Native.Table.FtsQuery.MultiMatch"]
+pub struct MultiMatchQuery {
+    pub terms: String,
+    pub columns: Vec<String>,
+    pub boosts: Option<Vec<f32>>,
+    pub operator: Operator,
+}
+
+#[derive(NifStruct, Clone, Debug)]
+#[module = "ElixirLanceDB.Native.Table.FtsQuery.Boolean"]
+pub struct BooleanQuery {
+    pub must: Vec<FtsQuery>,
+    pub should: Vec<FtsQuery>,
+    pub must_not: Vec<FtsQuery>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BoostQuery {
+    pub positive: Box<FtsQuery>,
+    pub negative: Box<FtsQuery>,
+    pub negative_boost: Option<f32>,
+}
+
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum Operator {
+    And,
+    Or,
+}
+
+impl FtsQuery {
+    // The positive terms of the query, as handed to rerankers
+    pub fn text(&self) -> String {
+        match self {
+            FtsQuery::Terms(terms) => terms.clone(),
+            FtsQuery::Match(query) => query.terms.clone(),
+            FtsQuery::Phrase(query) => query.terms.clone(),
+            FtsQuery::MultiMatch(query) => query.terms.clone(),
+            FtsQuery::Boolean(query) => query
+                .must
+                .iter()
+                .chain(query.should.iter())
+                .map(|q| q.text())
+                .collect::<Vec<String>>()
+                .join(" "),
+            FtsQuery::Boost(query) => query.positive.text(),
+        }
+    }
+}
+
+impl Decoder<'_> for FtsQuery {
+    fn decode(term: Term) -> NifResult<Self> {
+        if term.is_binary() {
+            return Ok(FtsQuery::Terms(term.decode()?));
+        }
+
+        let query_type = term.map_get(atoms::query_type())?.atom_to_string()?;
+        let result = match query_type.as_str() {
+            "match" => FtsQuery::Match(term.decode()?),
+            "phrase" => FtsQuery::Phrase(term.decode()?),
+            "multi_match" => FtsQuery::MultiMatch(term.decode()?),
+            "boolean" => FtsQuery::Boolean(term.decode()?),
+            "boost" => FtsQuery::Boost(term.decode()?),
+            _ => return Err(rustler::Error::BadArg),
+        };
+
+        Ok(result)
+    }
 }
 
-impl Into<LanceFullTextSearchQuery> for FullTextSearchQuery {
-    fn into(self) -> LanceFullTextSearchQuery {
-        LanceFullTextSearchQuery {
-            // columns: self.columns,
-            query: FtsQuery::Match(
-                MatchQuery::new(self.query).with_column(Some(self.columns[0].clone())),
+impl Encoder for FtsQuery {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let (query_type, term) = match self {
+            FtsQuery::Terms(terms) => return terms.encode(env),
+            FtsQuery::Match(query) => (atoms::match_(), query.encode(env)),
+            FtsQuery::Phrase(query) => (atoms::phrase(), query.encode(env)),
+            FtsQuery::MultiMatch(query) => (atoms::multi_match(), query.encode(env)),
+            FtsQuery::Boolean(query) => (atoms::boolean(), query.encode(env)),
+            FtsQuery::Boost(query) => (atoms::boost(), query.encode(env)),
+        };
+
+        term.map_put(atoms::query_type(), query_type)
+            .unwrap_or(term)
+    }
+}
+
+impl Decoder<'_> for BoostQuery {
+    fn decode(term: Term) -> NifResult<Self> {
+        let positive: FtsQuery = term.map_get(atoms::positive())?.decode()?;
+        let negative: FtsQuery = term.map_get(atoms::negative())?.decode()?;
+        let negative_boost: Option<f32> = term
+            .map_get(atoms::negative_boost())
+            .ok()
+            .and_then(|s| s.decode().ok());
+
+        Ok(BoostQuery {
+            positive: Box::new(positive),
+            negative: Box::new(negative),
+            negative_boost,
+        })
+    }
+}
+
+impl Encoder for BoostQuery {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let mut map = make_ex_struct(env, "Elixir.ElixirLanceDB.Native.Table.FtsQuery.Boost")
+            .unwrap_or_else(|_| Term::map_new(env));
+        map = map
+            .map_put(atoms::positive(), self.positive.encode(env))
+            .unwrap_or(map);
+        map = map
+            .map_put(atoms::negative(), self.negative.encode(env))
+            .unwrap_or(map);
+        map = map
+            .map_put(atoms::negative_boost(), self.negative_boost.encode(env))
+            .unwrap_or(map);
+        map
+    }
+}
+
+impl TryFrom<FtsQuery> for LanceFtsQuery {
+    type Error = Error;
+
+    fn try_from(value: FtsQuery) -> Result<Self> {
+        let query = match value {
+            FtsQuery::Terms(terms) => LanceFtsQuery::Match(LanceMatchQuery::new(terms)),
+            FtsQuery::Match(query) => LanceFtsQuery::Match(query.into()),
+            FtsQuery::Phrase(query) => LanceFtsQuery::Phrase(
+                LancePhraseQuery::new(query.terms)
+                    .with_column(query.column)
+                    .with_slop(query.slop),
             ),
-            limit: self.limit,
-            wand_factor: self.wand_factor,
+            FtsQuery::MultiMatch(query) => LanceFtsQuery::MultiMatch(query.try_into()?),
+            FtsQuery::Boolean(query) => LanceFtsQuery::Boolean(query.try_into()?),
+            FtsQuery::Boost(query) => LanceFtsQuery::Boost(LanceBoostQuery::new(
+                (*query.positive).try_into()?,
+                (*query.negative).try_into()?,
+                query.negative_boost,
+            )),
+        };
+
+        Ok(query)
+    }
+}
+
+impl Into<LanceMatchQuery> for MatchQuery {
+    fn into(self) -> LanceMatchQuery {
+        LanceMatchQuery::new(self.terms)
+            .with_column(self.column)
+            .with_boost(self.boost)
+            .with_fuzziness(self.fuzziness)
+            .with_max_expansions(self.max_expansions)
+            .with_operator(self.operator.into())
+    }
+}
+
+impl TryFrom<MultiMatchQuery> for LanceMultiMatchQuery {
+    type Error = Error;
+
+    fn try_from(value: MultiMatchQuery) -> Result<Self> {
+        if value.columns.is_empty() {
+            return Err(Error::InvalidInput {
+                message: "multi match query requires at least one column".to_string(),
+            });
+        }
+
+        let boosts = match value.boosts {
+            Some(boosts) if boosts.len() != value.columns.len() => {
+                return Err(Error::InvalidInput {
+                    message: format!(
+                        "multi match query has {} columns but {} boosts",
+                        value.columns.len(),
+                        boosts.len()
+                    ),
+                })
+            }
+            Some(boosts) => boosts,
+            None => vec![1.0; value.columns.len()],
+        };
+
+        let match_queries = value
+            .columns
+            .into_iter()
+            .zip(boosts)
+            .map(|(column, boost)| {
+                LanceMatchQuery::new(value.terms.clone())
+                    .with_column(Some(column))
+                    .with_boost(boost)
+                    .with_operator(value.operator.into())
+            })
+            .collect();
+
+        Ok(LanceMultiMatchQuery { match_queries })
+    }
+}
+
+impl TryFrom<BooleanQuery> for LanceBooleanQuery {
+    type Error = Error;
+
+    fn try_from(value: BooleanQuery) -> Result<Self> {
+        let mut clauses: Vec<(Occur, LanceFtsQuery)> = vec![];
+
+        for query in value.must {
+            clauses.push((Occur::Must, query.try_into()?));
+        }
+
+        for query in value.should {
+            clauses.push((Occur::Should, query.try_into()?));
+        }
+
+        for query in value.must_not {
+            clauses.push((Occur::MustNot, query.try_into()?));
+        }
+
+        Ok(LanceBooleanQuery::new(clauses))
+    }
+}
+
+impl Into<LanceOperator> for Operator {
+    fn into(self) -> LanceOperator {
+        match self {
+            Operator::And => LanceOperator::And,
+            Operator::Or => LanceOperator::Or,
         }
     }
 }
@@ -0,0 +1,471 @@
+// Parses Lucene-style query strings such as `title:"vector db" AND -draft body:lance~1`
+// into the FtsQuery tree. Clause occurrence follows the classic Lucene rules: clauses are
+// optional by default, `+` or AND makes them required and `-`, `!` or NOT excludes them.
+
+use crate::error::Error;
+
+use super::fts::{BooleanQuery, FtsQuery, MatchQuery, MultiMatchQuery, Operator, PhraseQuery};
+
+const DEFAULT_MAX_EXPANSIONS: usize = 50;
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl From<ParseError> for Error {
+    fn from(value: ParseError) -> Self {
+        Error::FtsQueryParse {
+            message: value.message,
+            position: value.position,
+        }
+    }
+}
+
+pub fn parse(input: &str, default_columns: &[String]) -> Result<FtsQuery, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        cursor: 0,
+        end: input.len(),
+    };
+
+    let query = parser.parse_query(default_columns, None)?;
+
+    match parser.peek() {
+        None => Ok(query),
+        Some(token) => Err(ParseError {
+            message: format!("unexpected {}", token.kind.describe()),
+            position: token.position,
+        }),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Phrase(String),
+    Colon,
+    Tilde,
+    Caret,
+    Plus,
+    Minus,
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Word(word) => format!("term '{}'", word),
+            TokenKind::Phrase(phrase) => format!("phrase \"{}\"", phrase),
+            TokenKind::Colon => "':'".to_string(),
+            TokenKind::Tilde => "'~'".to_string(),
+            TokenKind::Caret => "'^'".to_string(),
+            TokenKind::Plus => "'+'".to_string(),
+            TokenKind::Minus => "'-'".to_string(),
+            TokenKind::LParen => "'('".to_string(),
+            TokenKind::RParen => "')'".to_string(),
+            TokenKind::And => "AND".to_string(),
+            TokenKind::Or => "OR".to_string(),
+            TokenKind::Not => "NOT".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn is_special(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | ':' | '~' | '^' | '"')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens: Vec<Token> = vec![];
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let (position, c) = chars[idx];
+        let next = chars.get(idx + 1).map(|(_, c)| *c);
+
+        let simple = match (c, next) {
+            ('(', _) => Some((TokenKind::LParen, 1)),
+            (')', _) => Some((TokenKind::RParen, 1)),
+            (':', _) => Some((TokenKind::Colon, 1)),
+            ('~', _) => Some((TokenKind::Tilde, 1)),
+            ('^', _) => Some((TokenKind::Caret, 1)),
+            ('+', _) => Some((TokenKind::Plus, 1)),
+            ('-', _) => Some((TokenKind::Minus, 1)),
+            ('!', _) => Some((TokenKind::Not, 1)),
+            ('&', Some('&')) => Some((TokenKind::And, 2)),
+            ('|', Some('|')) => Some((TokenKind::Or, 2)),
+            _ => None,
+        };
+
+        if let Some((kind, width)) = simple {
+            tokens.push(Token { kind, position });
+            idx += width;
+            continue;
+        }
+
+        if c.is_whitespace() {
+            idx += 1;
+            continue;
+        }
+
+        if c == '"' {
+            let mut phrase = String::new();
+            idx += 1;
+            loop {
+                match chars.get(idx) {
+                    None => {
+                        return Err(ParseError {
+                            message: "unterminated phrase".to_string(),
+                            position,
+                        })
+                    }
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) if idx + 1 < chars.len() => {
+                        phrase.push(chars[idx + 1].1);
+                        idx += 2;
+                    }
+                    Some((_, c)) => {
+                        phrase.push(*c);
+                        idx += 1;
+                    }
+                }
+            }
+            idx += 1;
+            tokens.push(Token {
+                kind: TokenKind::Phrase(phrase),
+                position,
+            });
+            continue;
+        }
+
+        let mut word = String::new();
+        while idx < chars.len() && !is_special(chars[idx].1) {
+            match chars[idx].1 {
+                '\\' if idx + 1 < chars.len() => {
+                    word.push(chars[idx + 1].1);
+                    idx += 2;
+                }
+                c => {
+                    word.push(c);
+                    idx += 1;
+                }
+            }
+        }
+
+        let kind = match word.as_str() {
+            "AND" => TokenKind::And,
+            "OR" => TokenKind::Or,
+            "NOT" => TokenKind::Not,
+            _ => TokenKind::Word(word),
+        };
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Occurrence {
+    Must,
+    Should,
+    MustNot,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    cursor: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor)
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.peek().map(|token| &token.kind)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.cursor).cloned();
+        self.cursor += 1;
+        token
+    }
+
+    fn position(&self) -> usize {
+        self.peek().map(|token| token.position).unwrap_or(self.end)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            message: message.into(),
+            position: self.position(),
+        }
+    }
+
+    // `group_start` is the position of the opening parenthesis when parsing a group
+    fn parse_query(
+        &mut self,
+        default_columns: &[String],
+        group_start: Option<usize>,
+    ) -> Result<FtsQuery, ParseError> {
+        let start = self.position();
+        let mut clauses: Vec<(Occurrence, FtsQuery)> = vec![];
+
+        loop {
+            match self.peek_kind() {
+                None => break,
+                Some(TokenKind::RParen) if group_start.is_some() => break,
+                _ => {}
+            }
+
+            let conjunction = match self.peek_kind() {
+                Some(TokenKind::And) | Some(TokenKind::Or) => self.advance().map(|t| t.kind),
+                _ => None,
+            };
+
+            if let Some(kind) = &conjunction {
+                if clauses.is_empty() {
+                    return Err(ParseError {
+                        message: format!("expected a clause before {}", kind.describe()),
+                        position: start,
+                    });
+                }
+            }
+
+            let modifier = match self.peek_kind() {
+                Some(TokenKind::Plus) => Some(Occurrence::Must),
+                Some(TokenKind::Minus) | Some(TokenKind::Not) => Some(Occurrence::MustNot),
+                _ => None,
+            };
+
+            if modifier.is_some() {
+                self.advance();
+            }
+
+            let query = self.parse_clause(default_columns)?;
+
+            if conjunction == Some(TokenKind::And) {
+                if let Some(previous) = clauses.last_mut() {
+                    if previous.0 == Occurrence::Should {
+                        previous.0 = Occurrence::Must;
+                    }
+                }
+            }
+
+            let occurrence = match (modifier, &conjunction) {
+                (Some(occurrence), _) => occurrence,
+                (None, Some(TokenKind::And)) => Occurrence::Must,
+                _ => Occurrence::Should,
+            };
+
+            clauses.push((occurrence, query));
+        }
+
+        if clauses.is_empty() {
+            return Err(ParseError {
+                message: "expected a term, phrase or group".to_string(),
+                position: group_start.unwrap_or(start),
+            });
+        }
+
+        if clauses.len() == 1 && clauses[0].0 != Occurrence::MustNot {
+            return Ok(clauses.remove(0).1);
+        }
+
+        let mut boolean = BooleanQuery {
+            must: vec![],
+            should: vec![],
+            must_not: vec![],
+        };
+
+        for (occurrence, query) in clauses {
+            match occurrence {
+                Occurrence::Must => boolean.must.push(query),
+                Occurrence::Should => boolean.should.push(query),
+                Occurrence::MustNot => boolean.must_not.push(query),
+            }
+        }
+
+        Ok(FtsQuery::Boolean(boolean))
+    }
+
+    fn parse_clause(&mut self, default_columns: &[String]) -> Result<FtsQuery, ParseError> {
+        let token = match self.advance() {
+            Some(token) => token,
+            None => return Err(self.error("expected a term, phrase or group")),
+        };
+
+        match token.kind {
+            TokenKind::Word(field) if self.peek_kind() == Some(&TokenKind::Colon) => {
+                self.advance();
+                self.parse_clause(&[field])
+            }
+            TokenKind::Word(terms) => {
+                let (fuzziness, boost) = self.parse_term_modifiers()?;
+                Ok(term_query(terms, default_columns, fuzziness, boost))
+            }
+            TokenKind::Phrase(terms) => {
+                let slop = self.parse_phrase_modifiers()?;
+                Ok(phrase_query(terms, default_columns, slop))
+            }
+            TokenKind::LParen => {
+                let query = self.parse_query(default_columns, Some(token.position))?;
+                match self.advance() {
+                    Some(Token {
+                        kind: TokenKind::RParen,
+                        ..
+                    }) => {}
+                    _ => {
+                        return Err(ParseError {
+                            message: "missing closing ')'".to_string(),
+                            position: token.position,
+                        })
+                    }
+                }
+
+                match self.peek_kind() {
+                    Some(TokenKind::Tilde) | Some(TokenKind::Caret) => {
+                        Err(self.error("modifiers are not supported on groups"))
+                    }
+                    _ => Ok(query),
+                }
+            }
+            kind => Err(ParseError {
+                message: format!("unexpected {}", kind.describe()),
+                position: token.position,
+            }),
+        }
+    }
+
+    fn parse_term_modifiers(&mut self) -> Result<(Option<u32>, f32), ParseError> {
+        let mut fuzziness = Some(0);
+        let mut boost = 1.0;
+
+        loop {
+            match self.peek_kind() {
+                Some(TokenKind::Tilde) => {
+                    let tilde_position = self.position();
+                    self.advance();
+                    // a bare `~` lets Lance choose the edit distance, anything written
+                    // right after it has to be a whole number of edits
+                    fuzziness = match self.peek() {
+                        Some(Token {
+                            kind: TokenKind::Word(_),
+                            position,
+                        }) if *position == tilde_position + 1 => {
+                            Some(self.parse_number::<u32>("'~'")?)
+                        }
+                        _ => None,
+                    };
+                }
+                Some(TokenKind::Caret) => {
+                    self.advance();
+                    boost = self.parse_number("'^'")?;
+                }
+                _ => return Ok((fuzziness, boost)),
+            }
+        }
+    }
+
+    fn parse_phrase_modifiers(&mut self) -> Result<u32, ParseError> {
+        let mut slop = 0;
+
+        loop {
+            match self.peek_kind() {
+                Some(TokenKind::Tilde) => {
+                    self.advance();
+                    slop = self.parse_number::<u32>("'~'")?;
+                }
+                Some(TokenKind::Caret) => {
+                    return Err(self.error("boosting is not supported on phrases"));
+                }
+                _ => return Ok(slop),
+            }
+        }
+    }
+
+    fn parse_number<T: std::str::FromStr>(&mut self, after: &str) -> Result<T, ParseError> {
+        let position = self.position();
+        match self.advance() {
+            Some(Token {
+                kind: TokenKind::Word(word),
+                ..
+            }) => word.parse::<T>().map_err(|_| ParseError {
+                message: format!("expected a number after {}, got '{}'", after, word),
+                position,
+            }),
+            _ => Err(ParseError {
+                message: format!("expected a number after {}", after),
+                position,
+            }),
+        }
+    }
+}
+
+fn term_query(terms: String, columns: &[String], fuzziness: Option<u32>, boost: f32) -> FtsQuery {
+    let match_query = |column: Option<String>| MatchQuery {
+        terms: terms.clone(),
+        column,
+        boost,
+        fuzziness,
+        max_expansions: DEFAULT_MAX_EXPANSIONS,
+        operator: Operator::Or,
+    };
+
+    match columns {
+        [] => FtsQuery::Match(match_query(None)),
+        [column] => FtsQuery::Match(match_query(Some(column.clone()))),
+        _ if fuzziness == Some(0) => FtsQuery::MultiMatch(MultiMatchQuery {
+            terms: terms.clone(),
+            columns: columns.to_vec(),
+            boosts: Some(vec![boost; columns.len()]),
+            operator: Operator::Or,
+        }),
+        // multi match queries can't be fuzzy, so match each column on its own
+        _ => FtsQuery::Boolean(BooleanQuery {
+            must: vec![],
+            should: columns
+                .iter()
+                .map(|column| FtsQuery::Match(match_query(Some(column.clone()))))
+                .collect(),
+            must_not: vec![],
+        }),
+    }
+}
+
+fn phrase_query(terms: String, columns: &[String], slop: u32) -> FtsQuery {
+    let phrase = |column: Option<String>| {
+        FtsQuery::Phrase(PhraseQuery {
+            terms: terms.clone(),
+            column,
+            slop,
+        })
+    };
+
+    match columns {
+        [] => phrase(None),
+        [column] => phrase(Some(column.clone())),
+        _ => FtsQuery::Boolean(BooleanQuery {
+            must: vec![],
+            should: columns
+                .iter()
+                .map(|column| phrase(Some(column.clone())))
+                .collect(),
+            must_not: vec![],
+        }),
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::{HashMap, HashSet};
+
+use lance_index::DatasetIndexExt;
+use lancedb::{
+    index::{scalar::FtsIndexBuilder as LanceFtsIndexBuilder, IndexConfig, IndexType},
+    Table,
+};
+use rustler::NifStruct;
+use tantivy::tokenizer::TextAnalyzer;
+
+use crate::{
+    error::{Error, Result},
+    rustler_arrow::term_from_arrow::ReturnableTerm,
+    table::index::fts::tokenize,
+};
+
+use super::fts::{FtsQuery, FullTextSearchQuery};
+
+pub const HIGHLIGHTS_COLUMN: &str = "_highlights";
+
+#[derive(NifStruct, Clone, Debug)]
+#[module = "ElixirLanceDB.Native.Table.FullTextSearchQueryRequest.Highlight"]
+pub struct HighlightConfig {
+    pub columns: Vec<String>,
+    pub pre_tag: String,
+    pub post_tag: String,
+    pub fragment_size: Option<usize>,
+    pub max_fragments: usize,
+}
+
+pub struct Highlighter {
+    config: HighlightConfig,
+    columns: HashMap<String, ColumnTerms>,
+}
+
+// A highlighted column's index analyzer and the query analyzed with it
+struct ColumnTerms {
+    analyzer: TextAnalyzer,
+    // tokens highlighted wherever they appear
+    terms: HashSet<String>,
+    // token sequences highlighted as a whole, each token with its position
+    // relative to the first
+    phrases: Vec<Vec<(String, usize)>>,
+}
+
+impl Highlighter {
+    pub async fn load(
+        table: &Table,
+        config: HighlightConfig,
+        query: &FullTextSearchQuery,
+    ) -> Result<Self> {
+        let columns = match (config.columns.is_empty(), query.columns.is_empty()) {
+            (false, _) => config.columns.clone(),
+            (true, false) => query.columns.clone(),
+            (true, true) => {
+                let mut columns: Vec<String> = vec![];
+                query_columns(&query.query, &mut columns);
+                columns
+            }
+        };
+
+        let indices = table.list_indices().await?;
+        let mut analyzed = HashMap::new();
+        for column in columns {
+            let mut analyzer = index_analyzer(table, &indices, &column).await?;
+            let mut texts: Vec<(String, bool)> = vec![];
+            query_terms(&query.query, &column, &mut texts);
+
+            let mut terms = HashSet::new();
+            let mut phrases = vec![];
+            for (text, is_phrase) in texts {
+                let tokens = tokenize(&mut analyzer, &text);
+                match tokens.first() {
+                    Some(first) if is_phrase && tokens.len() > 1 => {
+                        let start = first.position;
+                        phrases.push(
+                            tokens
+                                .into_iter()
+                                .map(|token| (token.text, token.position - start))
+                                .collect(),
+                        );
+                    }
+                    _ => terms.extend(tokens.into_iter().map(|token| token.text)),
+                }
+            }
+
+            analyzed.insert(
+                column,
+                ColumnTerms {
+                    analyzer,
+                    terms,
+                    phrases,
+                },
+            );
+        }
+
+        Ok(Highlighter {
+            config,
+            columns: analyzed,
+        })
+    }
+
+    pub fn highlight(&mut self, row: &mut HashMap<String, ReturnableTerm>) {
+        let mut highlights: HashMap<String, ReturnableTerm> = HashMap::new();
+
+        for (column, column_terms) in self.columns.iter_mut() {
+            let text = match row.get(column) {
+                Some(ReturnableTerm::Utf8(text)) => text,
+                _ => continue,
+            };
+
+            let spans = column_terms.spans(text);
+
+            if spans.is_empty() {
+                continue;
+            }
+
+            let fragments: Vec<String> = fragment_windows(
+                text,
+                &spans,
+                self.config.fragment_size,
+                self.config.max_fragments,
+            )
+            .into_iter()
+            .map(|window| render(text, window, &spans, &self.config))
+            .collect();
+
+            let offsets: Vec<ReturnableTerm> = spans
+                .iter()
+                .map(|(from, to)| {
+                    ReturnableTerm::List(vec![
+                        ReturnableTerm::Int64(*from as i64),
+                        ReturnableTerm::Int64(*to as i64),
+                    ])
+                })
+                .collect();
+
+            let mut highlight: HashMap<String, ReturnableTerm> = HashMap::new();
+            highlight.insert("fragments".to_string(), ReturnableTerm::ListUtf8(fragments));
+            highlight.insert("offsets".to_string(), ReturnableTerm::List(offsets));
+            highlights.insert(column.clone(), ReturnableTerm::Map(highlight));
+        }
+
+        row.insert(
+            HIGHLIGHTS_COLUMN.to_string(),
+            ReturnableTerm::Map(highlights),
+        );
+    }
+}
+
+impl ColumnTerms {
+    // Byte ranges of the matched terms and phrases, ordered and without overlaps
+    fn spans(&mut self, text: &str) -> Vec<(usize, usize)> {
+        let tokens = tokenize(&mut self.analyzer, text);
+        let mut spans: Vec<(usize, usize)> = tokens
+            .iter()
+            .filter(|token| self.terms.contains(&token.text))
+            .map(|token| (token.offset_from, token.offset_to))
+            .collect();
+
+        let by_position: HashMap<(usize, &str), usize> = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| ((token.position, token.text.as_str()), i))
+            .collect();
+
+        for phrase in self.phrases.iter() {
+            for first in tokens.iter() {
+                let matched: Option<Vec<usize>> = phrase
+                    .iter()
+                    .map(|(text, offset)| {
+                        by_position
+                            .get(&(first.position + offset, text.as_str()))
+                            .copied()
+                    })
+                    .collect();
+                if let Some(matched) = matched {
+                    let from = matched.iter().map(|i| tokens[*i].offset_from).min();
+                    let to = matched.iter().map(|i| tokens[*i].offset_to).max();
+                    if let (Some(from), Some(to)) = (from, to) {
+                        spans.push((from, to));
+                    }
+                }
+            }
+        }
+
+        spans.sort();
+        let mut merged: Vec<(usize, usize)> = vec![];
+        for (from, to) in spans {
+            match merged.last_mut() {
+                Some((_, end)) if from < *end => *end = (*end).max(to),
+                _ => merged.push((from, to)),
+            }
+        }
+        merged
+    }
+}
+
+// Builds the analyzer from the tokenizer the column's full text search index was
+// built with, so highlights line up with what actually matched
+async fn index_analyzer(
+    table: &Table,
+    indices: &[IndexConfig],
+    column: &str,
+) -> Result<TextAnalyzer> {
+    let index = indices
+        .iter()
+        .find(|index| matches!(index.index_type, IndexType::FTS) && index.columns == [column])
+        .ok_or_else(|| Error::InvalidInput {
+            message: format!(
+                "cannot highlight {}, which has no full text search index",
+                column
+            ),
+        })?;
+
+    // Lance doesn't document the shape of its index statistics, so an index whose
+    // tokenizer can't be read from them, or that of a remote table, is taken to
+    // use the default one
+    let params = match table.dataset() {
+        Some(wrapper) => {
+            let statistics = wrapper.get().await?.index_statistics(&index.name).await?;
+            serde_json::from_str::<serde_json::Value>(&statistics)
+                .ok()
+                .and_then(|statistics| statistics["indices"].get(0)?.get("params").cloned())
+                .and_then(|params| serde_json::from_value::<LanceFtsIndexBuilder>(params).ok())
+        }
+        None => None,
+    }
+    .unwrap_or_default();
+
+    params.tokenizer_configs.build().map_err(|e| Error::Lance {
+        message: e.to_string(),
+    })
+}
+
+fn query_columns(query: &FtsQuery, columns: &mut Vec<String>) {
+    let found: Vec<&String> = match query {
+        FtsQuery::Terms(_) => vec![],
+        FtsQuery::Match(query) => query.column.iter().collect(),
+        FtsQuery::Phrase(query) => query.column.iter().collect(),
+        FtsQuery::MultiMatch(query) => query.columns.iter().collect(),
+        FtsQuery::Boolean(query) => {
+            for q in query.must.iter().chain(query.should.iter()) {
+                query_columns(q, columns);
+            }
+            vec![]
+        }
+        FtsQuery::Boost(query) => {
+            query_columns(&query.positive, columns);
+            vec![]
+        }
+    };
+
+    for column in found {
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+}
+
+// Collects the texts a column is searched for, and whether each is a phrase,
+// skipping excluded and demoted clauses
+fn query_terms(query: &FtsQuery, column: &str, texts: &mut Vec<(String, bool)>) {
+    match query {
+        FtsQuery::Terms(terms) => texts.push((terms.clone(), false)),
+        FtsQuery::Match(query) if query.column.as_deref().map_or(true, |c| c == column) => {
+            texts.push((query.terms.clone(), false))
+        }
+        FtsQuery::Phrase(query) if query.column.as_deref().map_or(true, |c| c == column) => {
+            texts.push((query.terms.clone(), true))
+        }
+        FtsQuery::MultiMatch(query) if query.columns.iter().any(|c| c == column) => {
+            texts.push((query.terms.clone(), false))
+        }
+        FtsQuery::Boolean(query) => query
+            .must
+            .iter()
+            .chain(query.should.iter())
+            .for_each(|q| query_terms(q, column, texts)),
+        FtsQuery::Boost(query) => query_terms(&query.positive, column, texts),
+        _ => {}
+    }
+}
+
+fn fragment_windows(
+    text: &str,
+    spans: &[(usize, usize)],
+    fragment_size: Option<usize>,
+    max_fragments: usize,
+) -> Vec<(usize, usize)> {
+    let fragment_size = match fragment_size {
+        Some(size) => size,
+        None => return vec![(0, text.len())],
+    };
+
+    let mut windows: Vec<(usize, usize)> = vec![];
+
+    for (from, to) in spans.iter().copied() {
+        // a match the previous window reaches into is rendered whole in that window
+        if let Some((_, end)) = windows.last_mut() {
+            if from < *end {
+                *end = ceil_char_boundary(text, to.max(*end));
+                continue;
+            }
+        }
+
+        let previous_end = windows.last().map(|(_, end)| *end).unwrap_or(0);
+        if windows.len() >= max_fragments {
+            break;
+        }
+
+        // center the match in the window where there is room to do so
+        let padding = fragment_size.saturating_sub(to - from) / 2;
+        let start = floor_char_boundary(text, from.saturating_sub(padding).max(previous_end));
+        let end = ceil_char_boundary(text, (start + fragment_size).max(to).min(text.len()));
+        windows.push((start, end));
+    }
+
+    windows
+}
+
+fn render(
+    text: &str,
+    window: (usize, usize),
+    spans: &[(usize, usize)],
+    config: &HighlightConfig,
+) -> String {
+    let (start, end) = window;
+    let mut fragment = String::new();
+    let mut cursor = start;
+
+    for (from, to) in spans.iter().copied() {
+        if from < cursor || to > end {
+            continue;
+        }
+        fragment.push_str(&text[cursor..from]);
+        fragment.push_str(&config.pre_tag);
+        fragment.push_str(&text[from..to]);
+        fragment.push_str(&config.post_tag);
+        cursor = to;
+    }
+
+    fragment.push_str(&text[cursor..end]);
+    fragment
+}
+
+fn floor_char_boundary(text: &str, mut idx: usize) -> usize {
+    while idx > 0 && !text.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+fn ceil_char_boundary(text: &str, mut idx: usize) -> usize {
+    while idx < text.len() && !text.is_char_boundary(idx) {
+        idx += 1;
+    }
+    idx
+}
@@ -0,0 +1,398 @@
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+use arrow_array::{
+    cast::AsArray,
+    new_null_array,
+    types::{Float32Type, UInt64Type},
+    Float32Array, RecordBatch,
+};
+use arrow_cast::cast;
+use arrow_ord::sort::{sort_to_indices, SortOptions};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use arrow_select::{concat::concat_batches, take::take_record_batch};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use lancedb::{
+    arrow::RecordBatchStream,
+    query::{ExecutableQuery, Query, QueryBase, VectorQuery as LanceVectorQuery},
+    rerankers::{rrf::RRFReranker, Reranker},
+    Error as LanceError, Table,
+};
+use rustler::{Decoder, NifResult, NifStruct, NifUnitEnum, ResourceArc, Term};
+
+use crate::{
+    atoms,
+    error::{Error, Result},
+    runtime::get_runtime,
+    rustler_arrow::term_from_arrow::{from_arrow, ReturnableTerm},
+    table::{table_conn, TableResource},
+};
+
+use super::{
+    callback_reranker::{CallbackReranker, CallbackRerankerConfig},
+    fts::FullTextSearchQuery,
+    vector::VectorQueryRequest,
+};
+
+const DISTANCE_COLUMN: &str = "_distance";
+const SCORE_COLUMN: &str = "_score";
+pub const ROW_ID_COLUMN: &str = "_rowid";
+const RELEVANCE_SCORE_COLUMN: &str = "_relevance_score";
+
+// Lance's own default when a query has no limit
+pub const DEFAULT_LIMIT: usize = 10;
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn hybrid_search_with_reranker(
+    table: ResourceArc<TableResource>,
+    request: VectorQueryRequest,
+) -> Result<Vec<HashMap<String, ReturnableTerm>>> {
+    let limit = request.base.limit.unwrap_or(DEFAULT_LIMIT);
+    let offset = request.base.offset.unwrap_or(0);
+    let query_text = hybrid_fts(&request)?.query.text();
+    let reranker = request.reranker.clone().into_reranker()?;
+    let norm = request.norm;
+
+    let table = table_conn(table)?;
+    let (vector_query, fts_query) = hybrid_legs(&table, &request)?;
+
+    let results = get_runtime().block_on(async {
+        let vector_stream = vector_query.execute().await?;
+        let vector_schema = vector_stream.schema();
+        let vector_batches: Vec<RecordBatch> = vector_stream.try_collect().await?;
+
+        let fts_stream = fts_query.execute().await?;
+        let fts_schema = fts_stream.schema();
+        let fts_batches: Vec<RecordBatch> = fts_stream.try_collect().await?;
+
+        let vector_results = concat_batches(&vector_schema, &vector_batches)?;
+        let fts_results = concat_batches(&fts_schema, &fts_batches)?;
+
+        // rerankers merge both legs, so they have to share a schema
+        let schema = merged_schema(&vector_schema, &fts_schema);
+        let vector_results = normalize(
+            project(&schema, &vector_results)?,
+            DISTANCE_COLUMN,
+            norm,
+            0.0,
+        )?;
+        let fts_results = normalize(project(&schema, &fts_results)?, SCORE_COLUMN, norm, 1.0)?;
+
+        let mut results = reranker
+            .rerank_hybrid(&query_text, vector_results, fts_results)
+            .await?;
+
+        results = page(results, offset, limit);
+
+        if !request.base.with_row_id {
+            let row_id_index = results.schema().index_of(ROW_ID_COLUMN)?;
+            results.remove_column(row_id_index);
+        }
+
+        from_arrow(vec![results])
+    })?;
+
+    Ok(results)
+}
+
+fn hybrid_fts(request: &VectorQueryRequest) -> Result<FullTextSearchQuery> {
+    request
+        .base
+        .full_text_search
+        .clone()
+        .ok_or_else(|| Error::InvalidInput {
+            message: "hybrid search requires a full text search query".to_string(),
+        })
+}
+
+// Builds the vector and full text search legs, each with its own candidate limit
+pub fn hybrid_legs(
+    table: &Table,
+    request: &VectorQueryRequest,
+) -> Result<(LanceVectorQuery, Query)> {
+    let fts = hybrid_fts(request)?;
+    let limit = request.base.limit.unwrap_or(DEFAULT_LIMIT);
+    let offset = request.base.offset.unwrap_or(0);
+    let vector_limit = request.vector_limit.unwrap_or(limit + offset);
+    let fts_limit = request
+        .fts_limit
+        .or(fts.limit.map(|limit| limit as usize))
+        .unwrap_or(limit + offset);
+
+    let mut fts_request = request.base.clone();
+    // the offset pages through reranked results, not through each leg
+    fts_request.limit = Some(fts_limit);
+    fts_request.offset = None;
+    if let Some(fts) = fts_request.full_text_search.as_mut() {
+        fts.limit = Some(fts_limit as i64);
+    }
+    let fts_query = fts_request.apply_to(table.query())?.with_row_id();
+
+    let mut vector_request = request.clone();
+    vector_request.base.full_text_search = None;
+    vector_request.base.limit = Some(vector_limit);
+    vector_request.base.offset = None;
+    let base_query = vector_request.base.clone().apply_to(table.query())?;
+    let mut vector_query = vector_request.apply_to(base_query)?.with_row_id();
+
+    if request.postfilter {
+        vector_query = vector_query.postfilter();
+    }
+
+    Ok((vector_query, fts_query))
+}
+
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum Normalization {
+    Score,
+    Rank,
+}
+
+#[derive(Clone)]
+pub enum RerankerConfig {
+    Rrf(RrfRerankerConfig),
+    LinearCombination(LinearCombinationRerankerConfig),
+    Callback(CallbackRerankerConfig),
+}
+
+#[derive(NifStruct, Clone, Debug)]
+#[module = "ElixirLanceDB.Native.Table.Reranker.RRF"]
+pub struct RrfRerankerConfig {
+    pub k: f32,
+}
+
+#[derive(NifStruct, Clone, Debug)]
+#[module = "ElixirLanceDB.Native.Table.Reranker.LinearCombination"]
+pub struct LinearCombinationRerankerConfig {
+    pub vector_weight: f32,
+    pub fts_weight: f32,
+}
+
+impl Decoder<'_> for RerankerConfig {
+    fn decode(term: Term) -> NifResult<Self> {
+        let reranker_type = term.map_get(atoms::reranker_type())?.atom_to_string()?;
+        let result = match reranker_type.as_str() {
+            "rrf" => RerankerConfig::Rrf(term.decode()?),
+            "linear_combination" => RerankerConfig::LinearCombination(term.decode()?),
+            "callback" => RerankerConfig::Callback(term.decode()?),
+            _ => return Err(rustler::Error::BadArg),
+        };
+
+        Ok(result)
+    }
+}
+
+impl RerankerConfig {
+    pub fn into_reranker(self) -> Result<Arc<dyn Reranker>> {
+        let reranker: Arc<dyn Reranker> = match self {
+            RerankerConfig::Rrf(cfg) => {
+                if cfg.k <= 0.0 {
+                    return Err(Error::InvalidInput {
+                        message: format!("rrf k must be positive, got {}", cfg.k),
+                    });
+                }
+                Arc::new(RRFReranker::new(cfg.k))
+            }
+            RerankerConfig::LinearCombination(cfg) => {
+                if cfg.vector_weight < 0.0
+                    || cfg.fts_weight < 0.0
+                    || cfg.vector_weight + cfg.fts_weight == 0.0
+                {
+                    return Err(Error::InvalidInput {
+                        message: format!(
+                            "linear combination weights must be non-negative and not both zero, got vector {} and fts {}",
+                            cfg.vector_weight, cfg.fts_weight
+                        ),
+                    });
+                }
+                Arc::new(LinearCombinationReranker {
+                    vector_weight: cfg.vector_weight,
+                    fts_weight: cfg.fts_weight,
+                })
+            }
+            RerankerConfig::Callback(cfg) => Arc::new(CallbackReranker::from(cfg)),
+        };
+
+        Ok(reranker)
+    }
+}
+
+// Scores rows by a weighted sum of the normalized vector similarity and FTS score.
+// A row missing from one leg contributes nothing for that leg.
+#[derive(Debug)]
+pub struct LinearCombinationReranker {
+    vector_weight: f32,
+    fts_weight: f32,
+}
+
+#[async_trait]
+impl Reranker for LinearCombinationReranker {
+    async fn rerank_hybrid(
+        &self,
+        _query: &str,
+        vector_results: RecordBatch,
+        fts_results: RecordBatch,
+    ) -> lancedb::Result<RecordBatch> {
+        let distances = values_by_row_id(&vector_results, DISTANCE_COLUMN)?;
+        let scores = values_by_row_id(&fts_results, SCORE_COLUMN)?;
+
+        let merged = self.merge_results(vector_results, fts_results)?;
+        let relevance = Float32Array::from_iter_values(row_ids(&merged)?.iter().map(|row_id| {
+            let similarity = 1.0 - distances.get(row_id).copied().unwrap_or(1.0);
+            let score = scores.get(row_id).copied().unwrap_or(0.0);
+            self.vector_weight * similarity + self.fts_weight * score
+        }));
+
+        sort_by_relevance(merged, relevance)
+    }
+}
+
+pub fn row_ids(batch: &RecordBatch) -> lancedb::Result<Vec<u64>> {
+    let column = batch
+        .column_by_name(ROW_ID_COLUMN)
+        .ok_or_else(|| LanceError::InvalidInput {
+            message: format!("expected {} column in reranker input", ROW_ID_COLUMN),
+        })?;
+
+    Ok(column.as_primitive::<UInt64Type>().values().to_vec())
+}
+
+fn values_by_row_id(batch: &RecordBatch, column: &str) -> lancedb::Result<HashMap<u64, f32>> {
+    let values = batch
+        .column_by_name(column)
+        .ok_or_else(|| LanceError::InvalidInput {
+            message: format!("expected {} column in reranker input", column),
+        })?
+        .as_primitive::<Float32Type>();
+
+    Ok(row_ids(batch)?
+        .into_iter()
+        .zip(values.iter())
+        .filter_map(|(row_id, value)| value.map(|value| (row_id, value)))
+        .collect())
+}
+
+// Appends the relevance column and orders rows by it, best first
+pub fn sort_by_relevance(
+    batch: RecordBatch,
+    relevance: Float32Array,
+) -> lancedb::Result<RecordBatch> {
+    let indices = sort_to_indices(
+        &relevance,
+        Some(SortOptions {
+            descending: true,
+            nulls_first: false,
+        }),
+        None,
+    )?;
+
+    let mut fields = batch.schema().fields().to_vec();
+    fields.push(Arc::new(Field::new(
+        RELEVANCE_SCORE_COLUMN,
+        DataType::Float32,
+        false,
+    )));
+    let mut columns = batch.columns().to_vec();
+    columns.push(Arc::new(relevance));
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+    Ok(take_record_batch(&batch, &indices)?)
+}
+
+pub fn page(batch: RecordBatch, offset: usize, limit: usize) -> RecordBatch {
+    let offset = offset.min(batch.num_rows());
+    let length = limit.min(batch.num_rows() - offset);
+    batch.slice(offset, length)
+}
+
+fn merged_schema(vector_schema: &SchemaRef, fts_schema: &SchemaRef) -> SchemaRef {
+    let mut fields: Vec<Field> = vector_schema
+        .fields()
+        .iter()
+        .map(|field| field.as_ref().clone().with_nullable(true))
+        .collect();
+
+    for field in fts_schema.fields() {
+        if fields.iter().all(|f| f.name() != field.name()) {
+            fields.push(field.as_ref().clone().with_nullable(true));
+        }
+    }
+
+    Arc::new(Schema::new(fields))
+}
+
+fn project(schema: &SchemaRef, batch: &RecordBatch) -> Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| match batch.column_by_name(field.name()) {
+            Some(column) => column.clone(),
+            None => new_null_array(field.data_type(), batch.num_rows()),
+        })
+        .collect();
+
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+// Rescales a leg's distance or score column into [0, 1], keeping its direction.
+// `degenerate` is used when every value is the same.
+fn normalize(
+    batch: RecordBatch,
+    column: &str,
+    norm: Normalization,
+    degenerate: f32,
+) -> Result<RecordBatch> {
+    let index = batch.schema().index_of(column)?;
+    let values = cast(batch.column(index), &DataType::Float32)?;
+    let mut values: Vec<Option<f32>> = values.as_primitive::<Float32Type>().iter().collect();
+
+    if let Normalization::Rank = norm {
+        values = rank(&values);
+    }
+
+    let (min, max) = values
+        .iter()
+        .flatten()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+            (min.min(*value), max.max(*value))
+        });
+    let range = max - min;
+    let normalized: Float32Array = values
+        .iter()
+        .map(|value| {
+            value.map(|v| {
+                if range > 0.0 {
+                    (v - min) / range
+                } else {
+                    degenerate
+                }
+            })
+        })
+        .collect();
+
+    let mut fields = batch.schema().fields().to_vec();
+    fields[index] = Arc::new(Field::new(column, DataType::Float32, true));
+    let mut columns = batch.columns().to_vec();
+    columns[index] = Arc::new(normalized);
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+// Replaces values with their ascending rank, so only ordering survives
+fn rank(values: &[Option<f32>]) -> Vec<Option<f32>> {
+    let mut order: Vec<usize> = (0..values.len()).filter(|i| values[*i].is_some()).collect();
+    order.sort_by(|a, b| {
+        values[*a]
+            .partial_cmp(&values[*b])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let mut ranks = vec![None; values.len()];
+    for (rank, i) in order.into_iter().enumerate() {
+        ranks[i] = Some(rank as f32);
+    }
+    ranks
+}
@@ -1,3 +1,9 @@
+mod callback_reranker;
+mod example;
 mod fts;
+mod fts_parser;
+mod highlight;
+mod hybrid;
 mod plain;
+mod plan;
 mod vector;
@@ -2,12 +2,12 @@ use crate::{
     error::Result,
     runtime::get_runtime,
     rustler_arrow::term_from_arrow::{from_arrow, ReturnableTerm},
-    table::{table_conn, TableResource},
+    table::{filter::Filter, table_conn, TableResource},
 };
 use arrow_array::RecordBatch;
 use futures::TryStreamExt;
-use lancedb::query::{ExecutableQuery, Query, QueryBase};
-use rustler::{NifStruct, ResourceArc};
+use lancedb::query::{ExecutableQuery, Query, QueryBase, Select};
+use rustler::{Decoder, NifResult, NifStruct, ResourceArc, Term};
 use std::{collections::HashMap, option::Option};
 
 use super::fts::FullTextSearchQuery;
@@ -20,7 +20,7 @@ fn query<'a>(
     let table = table_conn(table)?;
 
     let result: Vec<HashMap<String, ReturnableTerm>> = get_runtime().block_on(async {
-        let query = query_request.apply_to(table.query());
+        let query = query_request.apply_to(table.query())?;
         let results: Vec<RecordBatch> = query.execute().await?.try_collect().await?;
         from_arrow(results)
     })?;
@@ -29,24 +29,64 @@ fn query<'a>(
 }
 
 #[derive(NifStruct, Clone, Debug)]
+#[rustler(decode)]
 #[module = "ElixirLanceDB.Native.Table.QueryRequest"]
 pub struct QueryRequest {
     pub filter: Option<QueryFilter>,
     pub limit: Option<usize>,
     pub full_text_search: Option<FullTextSearchQuery>,
+    pub select: Option<QuerySelect>,
+    pub offset: Option<usize>,
+    pub with_row_id: bool,
+    pub fast_search: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum QuerySelect {
+    Columns(Vec<String>),
+    // alias => SQL expression
+    Dynamic(Vec<(String, String)>),
+}
+
+impl Decoder<'_> for QuerySelect {
+    fn decode(term: Term) -> NifResult<Self> {
+        if term.is_map() {
+            let mut expressions: Vec<(String, String)> = term
+                .decode::<HashMap<String, String>>()?
+                .into_iter()
+                .collect();
+            expressions.sort();
+            return Ok(QuerySelect::Dynamic(expressions));
+        }
+
+        match term.decode::<Vec<String>>() {
+            Ok(columns) => Ok(QuerySelect::Columns(columns)),
+            Err(_) => Ok(QuerySelect::Dynamic(term.decode()?)),
+        }
+    }
+}
+
+impl Into<Select> for QuerySelect {
+    fn into(self) -> Select {
+        match self {
+            QuerySelect::Columns(columns) => Select::Columns(columns),
+            QuerySelect::Dynamic(expressions) => Select::Dynamic(expressions),
+        }
+    }
 }
 
 #[derive(NifStruct, Clone, Debug)]
+#[rustler(decode)]
 #[module = "ElixirLanceDB.Native.Table.QueryFilter"]
 pub struct QueryFilter {
-    pub sql: Option<String>,
+    pub sql: Option<Filter>,
 }
 
 impl QueryRequest {
-    pub fn apply_to(self, mut query: Query) -> Query {
+    pub fn apply_to(self, mut query: Query) -> Result<Query> {
         query = match self.filter {
             Some(filter) => match filter.sql {
-                Some(sql) => query.only_if(sql),
+                Some(sql) => query.only_if(String::try_from(sql)?),
                 None => query,
             },
             None => query,
@@ -57,11 +97,29 @@ impl QueryRequest {
             None => query,
         };
 
+        query = match self.offset {
+            Some(offset) => query.offset(offset),
+            None => query,
+        };
+
+        if self.with_row_id {
+            query = query.with_row_id();
+        }
+
+        if self.fast_search {
+            query = query.fast_search();
+        }
+
+        query = match self.select {
+            Some(select) => query.select(select.into()),
+            None => query,
+        };
+
         query = match self.full_text_search {
-            Some(fts) => query.full_text_search(fts.into()),
+            Some(fts) => query.full_text_search(fts.try_into()?),
             None => query,
         };
 
-        query
+        Ok(query)
     }
 }
@@ -0,0 +1,92 @@
+use lancedb::{
+    query::{ExecutableQuery, VectorQuery as LanceVectorQuery},
+    Table,
+};
+use rustler::{Decoder, NifResult, ResourceArc, Term};
+
+use crate::{
+    error::{Error, Result},
+    runtime::get_runtime,
+    table::{table_conn, TableResource},
+};
+
+use super::{hybrid::hybrid_legs, plain::QueryRequest, vector::VectorQueryRequest};
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn explain_plan(
+    table: ResourceArc<TableResource>,
+    request: PlanRequest,
+    verbose: bool,
+) -> Result<String> {
+    plan(table, request, PlanMode::Explain { verbose })
+}
+
+// Runs the query and reports the plan annotated with runtime metrics
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn analyze_plan(table: ResourceArc<TableResource>, request: PlanRequest) -> Result<String> {
+    plan(table, request, PlanMode::Analyze)
+}
+
+pub enum PlanRequest {
+    Query(QueryRequest),
+    Vector(VectorQueryRequest),
+}
+
+impl Decoder<'_> for PlanRequest {
+    fn decode(term: Term) -> NifResult<Self> {
+        match term.decode::<VectorQueryRequest>() {
+            Ok(request) => Ok(PlanRequest::Vector(request)),
+            Err(_) => Ok(PlanRequest::Query(term.decode()?)),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum PlanMode {
+    Explain { verbose: bool },
+    Analyze,
+}
+
+fn plan(table: ResourceArc<TableResource>, request: PlanRequest, mode: PlanMode) -> Result<String> {
+    let table = table_conn(table)?;
+    get_runtime().block_on(async {
+        let plan = match request {
+            PlanRequest::Query(request) => {
+                describe(&request.apply_to(table.query())?, mode).await?
+            }
+            // hybrid search runs each leg as its own query
+            PlanRequest::Vector(request) if request.base.full_text_search.is_some() => {
+                let (vector_query, fts_query) = hybrid_legs(&table, &request)?;
+                format!(
+                    "Vector search:\n{}\nFull text search:\n{}",
+                    describe(&vector_query, mode).await?,
+                    describe(&fts_query, mode).await?
+                )
+            }
+            PlanRequest::Vector(request) => describe(&vector_query(&table, request)?, mode).await?,
+        };
+
+        Ok::<String, Error>(plan)
+    })
+}
+
+fn vector_query(table: &Table, request: VectorQueryRequest) -> Result<LanceVectorQuery> {
+    let postfilter = request.postfilter;
+    let base_query = request.base.clone().apply_to(table.query())?;
+    let mut vector_query = request.apply_to(base_query)?;
+
+    if postfilter {
+        vector_query = vector_query.postfilter();
+    }
+
+    Ok(vector_query)
+}
+
+async fn describe(query: &impl ExecutableQuery, mode: PlanMode) -> Result<String> {
+    let plan = match mode {
+        PlanMode::Explain { verbose } => query.explain_plan(verbose).await?,
+        PlanMode::Analyze => query.analyze_plan().await?,
+    };
+
+    Ok(plan)
+}
@@ -1,68 +1,132 @@
+use crate::error::{Error, Result};
 use crate::runtime::get_runtime;
 use crate::rustler_arrow::term_from_arrow::{from_arrow, ReturnableTerm};
 use crate::table::index::DistanceType;
 use crate::table::{table_conn, TableResource};
-use crate::error::{Error, Result};
-use arrow_array::RecordBatch;
+use arrow_array::{ArrayRef, Float16Array, Float32Array, Float64Array, RecordBatch, UInt8Array};
+use arrow_select::concat::concat_batches;
 use futures::TryStreamExt;
-use lancedb::query::{
-    ExecutableQuery, Query, QueryBase, QueryExecutionOptions, VectorQuery as LanceVectorQuery,
+use half::f16;
+use lancedb::{
+    arrow::RecordBatchStream,
+    query::{ExecutableQuery, Query, QueryBase, VectorQuery as LanceVectorQuery},
 };
-use rustler::{NifStruct, ResourceArc};
-use std::collections::HashMap;
+use rustler::{Decoder, NifResult, NifStruct, NifUnitEnum, ResourceArc, Term};
+use std::{collections::HashMap, sync::Arc};
 
-use super::plain::QueryRequest;
+use super::{
+    hybrid::{page, Normalization, RerankerConfig, DEFAULT_LIMIT},
+    plain::QueryRequest,
+};
+
+const QUERY_INDEX_COLUMN: &str = "query_index";
 
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn vector_search(
+pub fn vector_search_with_reranker(
     table: ResourceArc<TableResource>,
     request: VectorQueryRequest,
 ) -> Result<Vec<HashMap<String, ReturnableTerm>>> {
+    // fusion rerankers need both legs, so only a callback reranker applies here
+    let reranker = match request.reranker.clone() {
+        cfg @ RerankerConfig::Callback(_) => Some(cfg.into_reranker()?),
+        _ => None,
+    };
+    let limit = request.base.limit.unwrap_or(DEFAULT_LIMIT);
+    let offset = request.base.offset.unwrap_or(0);
+
     let table = table_conn(table)?;
     let result: Vec<HashMap<String, ReturnableTerm>> = get_runtime().block_on(async {
-        let base_query = request.clone().base.apply_to(table.query());
+        let mut request = request.clone();
+        if reranker.is_some() {
+            request.base.limit = Some(request.vector_limit.unwrap_or(limit + offset));
+            request.base.offset = None;
+        }
+
+        let base_query = request.clone().base.apply_to(table.query())?;
         let mut vector_query = request.clone().apply_to(base_query)?;
 
         if request.postfilter {
             vector_query = vector_query.postfilter();
         }
 
-        let record_batch: Vec<RecordBatch> = vector_query.execute().await?.try_collect().await?;
+        let stream = vector_query.execute().await?;
+        let schema = stream.schema();
+        let mut record_batch: Vec<RecordBatch> = stream.try_collect().await?;
+
+        if let Some(reranker) = reranker {
+            let candidates = concat_batches(&schema, &record_batch)?;
+            // a vector search has no query text to hand the reranker
+            let reranked = reranker.rerank_vector("", candidates).await?;
+            record_batch = vec![page(reranked, offset, limit)];
+        }
+
         let results = from_arrow(record_batch)?;
         Ok::<Vec<HashMap<String, ReturnableTerm>>, Error>(results)
     })?;
     Ok(result)
 }
 
+// Searches every vector in `query_vectors` in one pass, returning one result list per vector
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn hybrid_search(
+pub fn batch_vector_search(
     table: ResourceArc<TableResource>,
     request: VectorQueryRequest,
-) -> Result<Vec<HashMap<String, ReturnableTerm>>> {
+) -> Result<Vec<Vec<HashMap<String, ReturnableTerm>>>> {
+    let num_queries = request.query_vectors.len();
+    if num_queries == 0 {
+        return Err(Error::InvalidInput {
+            message: "batch vector search requires at least one query vector".to_string(),
+        });
+    }
+
     let table = table_conn(table)?;
-    let results = get_runtime().block_on(async {
-        let base_query = request.clone().base.apply_to(table.query());
+    let rows = get_runtime().block_on(async {
+        let base_query = request.clone().base.apply_to(table.query())?;
         let mut vector_query = request.clone().apply_to(base_query)?;
 
         if request.postfilter {
             vector_query = vector_query.postfilter();
         }
 
-        let record_batch: Vec<RecordBatch> = vector_query
-            .execute_hybrid(QueryExecutionOptions::default())
-            .await?
-            .try_collect()
-            .await?;
+        let record_batch: Vec<RecordBatch> = vector_query.execute().await?.try_collect().await?;
+        from_arrow(record_batch)
+    })?;
 
-        let results = from_arrow(record_batch)?;
+    let mut grouped: Vec<Vec<HashMap<String, ReturnableTerm>>> =
+        (0..num_queries).map(|_| vec![]).collect();
 
-        Ok::<Vec<HashMap<String, ReturnableTerm>>, Error>(results)
-    })?;
+    for mut row in rows {
+        // Lance only adds the query index column when searching more than one vector
+        let index = match row.remove(QUERY_INDEX_COLUMN) {
+            Some(ReturnableTerm::Int32(index)) if index >= 0 => index as usize,
+            None if num_queries == 1 => 0,
+            other => {
+                return Err(Error::Other {
+                    message: format!(
+                        "expected a {} column naming one of {} query vectors, got {:?}",
+                        QUERY_INDEX_COLUMN, num_queries, other
+                    ),
+                })
+            }
+        };
+        match grouped.get_mut(index) {
+            Some(group) => group.push(row),
+            None => {
+                return Err(Error::Other {
+                    message: format!(
+                        "{} {} is out of range for {} query vectors",
+                        QUERY_INDEX_COLUMN, index, num_queries
+                    ),
+                })
+            }
+        }
+    }
 
-    Ok(results)
+    Ok(grouped)
 }
 
 #[derive(NifStruct, Clone)]
+#[rustler(decode)]
 #[module = "ElixirLanceDB.Native.Table.VectorQueryRequest"]
 pub struct VectorQueryRequest {
     pub base: QueryRequest,
@@ -70,7 +134,9 @@ pub struct VectorQueryRequest {
 
     // Vector
     pub column: Option<String>,
-    pub query_vector: Vec<f32>,
+    pub query_vector: QueryVector,
+    pub query_vectors: Vec<QueryVector>,
+    pub vector_type: VectorType,
     pub nprobes: Option<usize>,
     pub lower_bound: Option<f32>,
     pub upper_bound: Option<f32>,
@@ -78,11 +144,32 @@ pub struct VectorQueryRequest {
     pub refine_factor: Option<u32>,
     pub distance_type: Option<DistanceType>,
     pub use_index: bool,
+
+    // Hybrid
+    pub reranker: RerankerConfig,
+    pub norm: Normalization,
+    pub vector_limit: Option<usize>,
+    pub fts_limit: Option<usize>,
 }
 
 impl VectorQueryRequest {
     pub fn apply_to(self, query: Query) -> Result<LanceVectorQuery> {
-        let mut vector_query = query.nearest_to(self.query_vector)?;
+        let vector_type = self.vector_type;
+        // lancedb scores several query vectors against a multivector column as one MaxSim query
+        let query_vectors = match (self.query_vectors.is_empty(), self.query_vector) {
+            (true, QueryVector::Multi(vectors)) => {
+                vectors.into_iter().map(QueryVector::Floats).collect()
+            }
+            (true, query_vector) => vec![query_vector],
+            (false, _) => self.query_vectors,
+        };
+
+        let mut query_vectors = query_vectors.into_iter();
+        let first = query_vectors.next().unwrap_or(QueryVector::Floats(vec![]));
+        let mut vector_query = query.nearest_to(first.into_array(vector_type)?)?;
+        for query_vector in query_vectors {
+            vector_query = vector_query.add_query_vector(query_vector.into_array(vector_type)?)?;
+        }
 
         if let Some(column) = self.column {
             vector_query = vector_query.column(&column);
@@ -113,3 +200,70 @@ impl VectorQueryRequest {
         Ok(vector_query)
     }
 }
+
+// Element type of the vector column being searched
+#[derive(NifUnitEnum, Clone, Copy, Debug)]
+pub enum VectorType {
+    Float16,
+    Float32,
+    Float64,
+    // packed bits, searched with hamming distance
+    Uint8,
+}
+
+#[derive(Clone, Debug)]
+pub enum QueryVector {
+    Floats(Vec<f64>),
+    Integers(Vec<u8>),
+    // one vector per token, scored against multivector columns with MaxSim
+    Multi(Vec<Vec<f64>>),
+    // taken as-is from an existing vector column
+    Array(ArrayRef),
+}
+
+impl Decoder<'_> for QueryVector {
+    fn decode(term: Term) -> NifResult<Self> {
+        if let Ok(values) = term.decode::<Vec<f64>>() {
+            return Ok(QueryVector::Floats(values));
+        }
+        if let Ok(values) = term.decode::<Vec<u8>>() {
+            return Ok(QueryVector::Integers(values));
+        }
+        Ok(QueryVector::Multi(term.decode()?))
+    }
+}
+
+impl QueryVector {
+    pub fn into_array(self, vector_type: VectorType) -> Result<ArrayRef> {
+        let values = match (self, vector_type) {
+            (QueryVector::Array(array), _) => return Ok(array),
+            (QueryVector::Multi(_), _) => {
+                return Err(Error::InvalidInput {
+                    message: "multivector queries cannot be batched".to_string(),
+                })
+            }
+            (QueryVector::Integers(values), VectorType::Uint8) => {
+                return Ok(Arc::new(UInt8Array::from(values)))
+            }
+            (QueryVector::Floats(values), _) => values,
+            (QueryVector::Integers(values), _) => values.into_iter().map(f64::from).collect(),
+        };
+
+        let array: ArrayRef = match vector_type {
+            VectorType::Float16 => Arc::new(Float16Array::from(
+                values.into_iter().map(f16::from_f64).collect::<Vec<f16>>(),
+            )),
+            VectorType::Float32 => Arc::new(Float32Array::from(
+                values.into_iter().map(|v| v as f32).collect::<Vec<f32>>(),
+            )),
+            VectorType::Float64 => Arc::new(Float64Array::from(values)),
+            VectorType::Uint8 => {
+                return Err(Error::InvalidInput {
+                    message: "uint8 query vectors must hold integers from 0 to 255".to_string(),
+                })
+            }
+        };
+
+        Ok(array)
+    }
+}
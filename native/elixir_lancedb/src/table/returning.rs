@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use arrow_array::{cast::AsArray, types::UInt64Type};
+use lance::{dataset::ROW_ID, Dataset};
+use rustler::NifUnitEnum;
+
+use crate::{
+    error::{Error, Result},
+    rustler_arrow::term_from_arrow::{from_arrow, ReturnableTerm},
+};
+
+// What a write returns about the rows it changes, besides their count
+#[derive(NifUnitEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Returning {
+    Count,
+    RowIds,
+    Rows,
+}
+
+pub struct Captured {
+    pub num_rows: u64,
+    pub row_ids: Option<Vec<u64>>,
+    pub rows: Option<Vec<HashMap<String, ReturnableTerm>>>,
+}
+
+impl Captured {
+    // Reads the rows matching `predicate` as of `dataset`, i.e. before they are changed
+    pub async fn read(
+        dataset: &Dataset,
+        predicate: Option<&str>,
+        returning: Returning,
+    ) -> Result<Captured> {
+        if returning == Returning::Count {
+            return Ok(Captured {
+                num_rows: dataset.count_rows(predicate.map(String::from)).await? as u64,
+                row_ids: None,
+                rows: None,
+            });
+        }
+
+        let mut scanner = dataset.scan();
+        if let Some(predicate) = predicate {
+            scanner.filter(predicate)?;
+        }
+        scanner.with_row_id();
+        if returning == Returning::RowIds {
+            // row ids come with any projection, so read the narrowest one
+            let first = dataset
+                .schema()
+                .fields
+                .first()
+                .ok_or_else(|| Error::InvalidInput {
+                    message: "the table has no columns".to_string(),
+                })?;
+            scanner.project(&[&first.name])?;
+        }
+        let batch = scanner.try_into_batch().await?;
+        let num_rows = batch.num_rows() as u64;
+
+        match returning {
+            Returning::RowIds => {
+                let row_ids = batch
+                    .column_by_name(ROW_ID)
+                    .ok_or_else(|| Error::Other {
+                        message: "the scan returned no row ids".to_string(),
+                    })?
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .to_vec();
+                Ok(Captured {
+                    num_rows,
+                    row_ids: Some(row_ids),
+                    rows: None,
+                })
+            }
+            _ => Ok(Captured {
+                num_rows,
+                row_ids: None,
+                rows: Some(from_arrow(vec![batch])?),
+            }),
+        }
+    }
+}
@@ -1,16 +1,26 @@
-use rustler::{Decoder, NifResult, NifStruct, ResourceArc, Term};
+use std::{collections::HashMap, sync::Arc};
+
+use lance::dataset::UpdateBuilder;
+use rustler::{Decoder, NifMap, NifResult, NifStruct, ResourceArc, Term};
 
 use crate::{
     atoms,
     error::{Error, Result},
     runtime::get_runtime,
+    rustler_arrow::term_from_arrow::ReturnableTerm,
 };
 
-use super::{table_conn, TableResource};
+use super::{
+    filter::Filter,
+    not_native,
+    returning::{Captured, Returning},
+    table_conn, TableResource,
+};
 
 pub struct UpdateConfig {
-    pub filter: Option<String>,
+    pub filter: Option<Filter>,
     pub columns: Vec<ColumnOperation>,
+    pub returning: Returning,
 }
 
 #[derive(NifStruct, Debug)]
@@ -20,42 +30,88 @@ pub struct ColumnOperation {
     pub operation: String,
 }
 
+#[derive(NifMap)]
+#[rustler(encode)]
+pub struct UpdateResult {
+    pub num_updated_rows: u64,
+    pub version: u64,
+    pub row_ids: Option<Vec<u64>>,
+    pub rows: Option<Vec<HashMap<String, ReturnableTerm>>>,
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn update(table: ResourceArc<TableResource>, update_config: UpdateConfig) -> Result<u64> {
+pub fn update(
+    table: ResourceArc<TableResource>,
+    update_config: UpdateConfig,
+) -> Result<UpdateResult> {
     let table = table_conn(table)?;
-    let result = get_runtime().block_on(async {
-        let mut update = table.update();
+    let filter = update_config.filter.map(String::try_from).transpose()?;
 
-        update = match update_config.filter {
-            Some(filter) => update.only_if(filter),
-            None => update,
-        };
+    get_runtime().block_on(async {
+        // only capturing the affected rows needs the dataset, so remote tables can still update
+        if update_config.returning == Returning::Count {
+            let mut update = table.update();
+            if let Some(filter) = filter {
+                update = update.only_if(filter);
+            }
+            for op in update_config.columns {
+                update = update.column(op.column, op.operation);
+            }
+            let result = update.execute().await?;
+            return Ok(UpdateResult {
+                num_updated_rows: result.rows_updated,
+                version: result.version,
+                row_ids: None,
+                rows: None,
+            });
+        }
 
-        let update = update_config.columns.iter().fold(update, |update_acc, op| {
-            update_acc.column(&op.column, &op.operation)
-        });
+        let wrapper = table.dataset().ok_or_else(not_native)?;
+        // held across the read and the update so both see the same version
+        let mut latest = wrapper.get_mut().await?;
 
-        let num_rows_affected = update.execute().await?;
+        // rows are read before the update commits, so they hold the old values
+        let captured = Captured::read(&latest, filter.as_deref(), update_config.returning).await?;
 
-        Ok::<u64, Error>(num_rows_affected)
-    })?;
+        let mut update = UpdateBuilder::new(Arc::new(latest.clone()));
+        if let Some(filter) = &filter {
+            update = update.update_where(filter)?;
+        }
+        for op in &update_config.columns {
+            update = update.set(&op.column, &op.operation)?;
+        }
+        let result = update.build()?.execute().await?;
+        let version = result.new_dataset.version().version;
+        *latest = result.new_dataset.as_ref().clone();
 
-    Ok(result)
+        Ok::<UpdateResult, Error>(UpdateResult {
+            num_updated_rows: result.rows_updated,
+            version,
+            row_ids: captured.row_ids,
+            rows: captured.rows,
+        })
+    })
 }
 
 impl Decoder<'_> for UpdateConfig {
     fn decode(term: Term) -> NifResult<Self> {
-        let filter: Option<String> = term
+        let filter: Option<Filter> = term
             .map_get(atoms::filter())
             .ok()
-            .and_then(|s| s.decode().ok());
+            // a filter that fails to decode must not be silently dropped
+            .map(|s| s.decode::<Option<Filter>>())
+            .transpose()?
+            .flatten();
 
         let columns: Vec<ColumnOperation> =
             term.map_get(atoms::columns()).and_then(|s| s.decode())?;
 
+        let returning: Returning = term.map_get(atoms::returning()).and_then(|s| s.decode())?;
+
         Ok(UpdateConfig {
             filter: filter,
             columns: columns,
+            returning,
         })
     }
 }
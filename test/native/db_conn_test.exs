@@ -3,6 +3,8 @@ defmodule ElixirLanceDB.NativeTest do
   alias ElixirLanceDB.Native
   alias ElixirLanceDB.Native.Schema
   alias ElixirLanceDB.Native.Schema.Field
+  alias ElixirLanceDB.Native.Table.CreateConfig
+  alias ElixirLanceDB.Native.TableNamesRequest
 
   describe "Native :: Database Connection" do
     setup do
@@ -29,6 +31,30 @@ defmodule ElixirLanceDB.NativeTest do
       assert table_names |> Enum.empty?()
     end
 
+    test "it can page through table names", %{conn: conn} do
+      for name <- ["a", "b", "c"] do
+        conn |> Native.create_empty_table(name, Schema.from([Field.utf8("foo")]))
+      end
+
+      assert {:ok, ["a", "b"]} == conn |> Native.table_names(TableNamesRequest.new(limit: 2))
+
+      assert {:ok, ["c"]} ==
+               conn |> Native.table_names(TableNamesRequest.new(start_after: "b", limit: 2))
+    end
+
+    test "it can list tables with summaries", %{conn: conn} do
+      {:ok, table} = conn |> Native.create_table("summarized", [%{"foo" => "bar"}])
+      table |> Native.create_index(["foo"])
+      conn |> Native.create_empty_table("unsummarized", Schema.from([Field.utf8("foo")]))
+
+      {:ok, [summary]} = conn |> Native.list_tables(TableNamesRequest.new(limit: 1))
+
+      assert %{name: "summarized", row_count: 1, fields: ["foo"], indices: ["foo_idx"]} =
+               summary
+
+      assert summary.version > 1
+    end
+
     test "it creates a table from schema", %{conn: conn} do
       schema =
         Schema.from([
@@ -60,6 +86,31 @@ defmodule ElixirLanceDB.NativeTest do
       assert result == items
     end
 
+    test "it can create tables in overwrite and exist_ok modes", %{conn: conn} do
+      items = [%{"foo" => "bar"}, %{"foo" => "baz"}]
+      {:ok, _} = conn |> Native.create_table("modes", items)
+
+      {:error, {:lance_table_already_exists, _}} = conn |> Native.create_table("modes", items)
+
+      {:ok, table} =
+        conn |> Native.create_table("modes", items, CreateConfig.new(mode: :exist_ok))
+
+      assert {:ok, 2} == table |> Native.count_rows()
+
+      {:error, {:lance_schema, _}} =
+        conn
+        |> Native.create_empty_table(
+          "modes",
+          Schema.from([Field.int32("foo")]),
+          CreateConfig.new(mode: :exist_ok)
+        )
+
+      overwrite = CreateConfig.new(mode: :overwrite)
+      {:ok, table} = conn |> Native.create_table("modes", [%{"foo" => "qux"}], overwrite)
+
+      assert {:ok, [%{"foo" => "qux"}]} == table |> Native.query()
+    end
+
     test "it can drop all tables", %{conn: conn} do
       conn |> Native.create_empty_table("table_to_drop", Schema.from([Field.utf8("foo")]))
       assert {:ok, ["table_to_drop"]} == conn |> Native.table_names()
@@ -77,6 +128,19 @@ defmodule ElixirLanceDB.NativeTest do
       assert {:ok, ["table_to_keep"]} == conn |> Native.table_names()
     end
 
+    test "it can check whether a table exists", %{conn: conn} do
+      conn |> Native.create_empty_table("docs_v1", Schema.from([Field.utf8("foo")]))
+      assert {:ok, true} == conn |> Native.table_exists("docs_v1")
+      assert {:ok, false} == conn |> Native.table_exists("docs_v2")
+    end
+
+    test "it reports renames the local store cannot do", %{conn: conn} do
+      conn |> Native.create_empty_table("docs_v1", Schema.from([Field.utf8("foo")]))
+
+      {:error, {:lance_not_supported, _}} =
+        conn |> Native.rename_table("docs_v1", "docs_v2")
+    end
+
     test "it can acquire an open table resource", %{conn: conn} do
       conn |> Native.create_table("to_open", [%{"foo" => "bar"}, %{"foo" => "baz"}])
       {:ok, table} = conn |> Native.open_table("to_open")
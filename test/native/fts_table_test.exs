@@ -1,6 +1,7 @@
 defmodule ElixirLanceDB.Native.FtsTableTest do
   use ExUnit.Case
   alias ElixirLanceDB.Native.Table.QueryRequest
+  alias ElixirLanceDB.Native.Table.FtsQuery
   alias ElixirLanceDB.Native.Table.Index
   alias ElixirLanceDB.Native
 
@@ -29,6 +30,184 @@ defmodule ElixirLanceDB.Native.FtsTableTest do
       [first | _] = result
       assert first["content"] == "content for row 56"
     end
+
+    test "it can skip unindexed rows with fast search", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      new_row = %{"content" => "unindexed zebra", "title" => "zebra", "vector" => create_vec(2)}
+      vectors |> Native.add([new_row])
+
+      query = QueryRequest.new() |> QueryRequest.fts("zebra", "content")
+      {:ok, [zebra]} = vectors |> Native.full_text_search(query)
+      assert zebra["content"] == "unindexed zebra"
+
+      assert {:ok, []} ==
+               vectors |> Native.full_text_search(query |> QueryRequest.fast_search())
+    end
+
+    test "it can highlight matched terms", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      query = QueryRequest.new() |> QueryRequest.fts("row 56", "content", highlight: true)
+      {:ok, [first | _]} = vectors |> Native.full_text_search(query)
+
+      assert first["_highlights"]["content"]["fragments"] == [
+               "content for <em>row</em> <em>56</em>"
+             ]
+
+      assert first["_highlights"]["content"]["offsets"] == [[12, 15], [16, 18]]
+    end
+
+    test "it highlights phrases whole and extends overlapping fragments", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      phrase = FtsQuery.phrase("row 56", column: "content")
+      query = QueryRequest.new() |> QueryRequest.fts_query(phrase, highlight: true)
+      {:ok, [first | _]} = vectors |> Native.full_text_search(query)
+      assert first["_highlights"]["content"]["offsets"] == [[12, 18]]
+      assert first["_highlights"]["content"]["fragments"] == ["content for <em>row 56</em>"]
+
+      terms =
+        QueryRequest.new()
+        |> QueryRequest.fts("for row 56", "content", highlight: [fragment_size: 6])
+      {:ok, [first | _]} = vectors |> Native.full_text_search(terms)
+      assert first["content"] == "content for row 56"
+      assert first["_highlights"]["content"]["offsets"] == [[8, 11], [12, 15], [16, 18]]
+
+      assert first["_highlights"]["content"]["fragments"] == [
+               " <em>for</em> <em>row</em>",
+               " <em>56</em>"
+             ]
+    end
+
+    test "it highlights with the tokenizer of the column's index", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts(stem: true))
+      query = QueryRequest.new() |> QueryRequest.fts("rows 56", "content", highlight: true)
+      {:ok, [first | _]} = vectors |> Native.full_text_search(query)
+      assert first["_highlights"]["content"]["offsets"] == [[12, 15], [16, 18]]
+
+      highlight = [columns: ["title"]]
+      unindexed = QueryRequest.new() |> QueryRequest.fts("rows", "content", highlight: highlight)
+      {:error, {:invalid_input, msg}} = vectors |> Native.full_text_search(unindexed)
+      assert msg =~ "no full text search index"
+    end
+
+    test "it searches every requested column", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      vectors |> Native.create_index(["title"], Index.fts())
+
+      content_only = QueryRequest.new() |> QueryRequest.fts("title", "content")
+      both = QueryRequest.new() |> QueryRequest.fts("title", ["content", "title"])
+
+      {:ok, content_only} = vectors |> Native.full_text_search(content_only)
+      {:ok, both} = vectors |> Native.full_text_search(both)
+
+      assert content_only == []
+      assert both |> length() > 0
+    end
+
+    test "it can perform structured phrase and boolean queries", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+
+      phrase =
+        QueryRequest.new()
+        |> QueryRequest.fts_query(FtsQuery.phrase("row 56", column: "content"))
+
+      {:ok, [first | _]} = vectors |> Native.full_text_search(phrase)
+      assert first["content"] == "content for row 56"
+
+      boolean =
+        FtsQuery.boolean(
+          must: [FtsQuery.match("content", column: "content")],
+          must_not: [FtsQuery.match("56", column: "content")]
+        )
+
+      {:ok, result} =
+        vectors |> Native.full_text_search(QueryRequest.new() |> QueryRequest.fts_query(boolean))
+
+      assert result |> length() > 0
+      refute result |> Enum.any?(&(&1["content"] == "content for row 56"))
+    end
+  end
+
+  describe "FTS Tokenizer ::" do
+    test "it previews tokenization with stemming and stop words" do
+      {:ok, tokens} =
+        Index.preview_tokenization("The Runners were running", stem: true, remove_stop_words: true)
+
+      assert tokens |> Enum.map(& &1.text) == ["runner", "run"]
+      assert %{text: "run", position: 3, offset_from: 17, offset_to: 24} = List.last(tokens)
+    end
+
+    test "it removes custom stop words" do
+      {:ok, tokens} = Index.preview_tokenization("content for row 56", stop_words: ["for", "row"])
+      assert tokens |> Enum.map(& &1.text) == ["content", "56"]
+    end
+
+    test "it rejects unsupported languages", %{table: vectors} do
+      assert {:error, {:invalid_input, message}} =
+               vectors |> Native.create_index(["content"], Index.fts(language: :klingon))
+
+      assert message =~ "english"
+    end
+
+    test "it can create an ngram index", %{table: vectors} do
+      {:ok, tokens} =
+        Index.preview_tokenization("row",
+          base_tokenizer: :ngram,
+          min_ngram_length: 2,
+          max_ngram_length: 3
+        )
+
+      assert tokens |> Enum.map(& &1.text) |> Enum.sort() == ["ow", "ro", "row"]
+
+      assert {:error, {:invalid_input, _}} =
+               Index.preview_tokenization("row",
+                 base_tokenizer: :ngram,
+                 min_ngram_length: 4,
+                 max_ngram_length: 3
+               )
+
+      {result, _} =
+        vectors |> Native.create_index(["content"], Index.fts(base_tokenizer: :ngram))
+
+      assert result == :ok
+    end
+  end
+
+  describe "FTS Query Parser ::" do
+    test "it parses a lucene-style query string" do
+      {:ok, query} = FtsQuery.parse(~s(title:"vector db" AND -draft body:lance~1), ["content"])
+
+      assert query == %FtsQuery.Boolean{
+               must: [%FtsQuery.Phrase{terms: "vector db", column: "title"}],
+               should: [%FtsQuery.Match{terms: "lance", column: "body", fuzziness: 1}],
+               must_not: [%FtsQuery.Match{terms: "draft", column: "content"}]
+             }
+    end
+
+    test "it returns positioned parse errors" do
+      assert {:error, {:fts_query_parse_error, %{position: 6}}} =
+               FtsQuery.parse(~s(title:"vector db))
+
+      assert {:error, {:fts_query_parse_error, %{position: 5, message: message}}} =
+               FtsQuery.parse("body:(lance OR vector")
+
+      assert message =~ "missing closing ')'"
+
+      assert {:error, {:fts_query_parse_error, %{position: 6, message: message}}} =
+               FtsQuery.parse("lance~0.8")
+
+      assert message =~ "expected a number after '~', got '0.8'"
+    end
+
+    test "it can search with a parsed query", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      {:ok, query} = FtsQuery.parse("+content -56 \"row 56\"", ["content"])
+
+      {:ok, result} =
+        vectors |> Native.full_text_search(QueryRequest.new() |> QueryRequest.fts_query(query))
+
+      assert result |> length() > 0
+      refute result |> Enum.any?(&(&1["content"] == "content for row 56"))
+    end
   end
 
   defp create_rows(num \\ 256, dim_times_8 \\ 2) when is_integer(num) do
@@ -37,6 +216,7 @@ defmodule ElixirLanceDB.Native.FtsTableTest do
     |> Enum.map(fn n ->
       %{
         "content" => "content for row #{n}",
+        "title" => "title of row #{n}",
         "vector" => create_vec(dim_times_8)
       }
     end)
@@ -3,11 +3,14 @@ defmodule ElixirNativeDB.Native.TableTest do
 
   alias ElixirLanceDB.Native.Schema.{NewColumnTransform, ColumnAlteration, Field}
   alias ElixirLanceDB.Native.Schema
+  alias ElixirLanceDB.Native.Schema.Infer
   alias ElixirLanceDB.Native.Table.OptimizeAction.All
   alias ElixirLanceDB.Native.Table.Index
   alias ElixirLanceDB.Native
   alias ElixirLanceDB.Native.Table.QueryRequest, as: QR
   alias ElixirLanceDB.Native.Table.UpdateConfig, as: UpCfg
+  alias ElixirLanceDB.Native.Table.{AddConfig, Filter, MergeInsertConfig}
+  alias ElixirLanceDB.Native.Table.UpdateConfig.ColumnOperation, as: ColumnOp
 
   setup do
     {:ok, conn} = Path.join(File.cwd!(), "data/testing") |> Native.connect()
@@ -69,6 +72,115 @@ defmodule ElixirNativeDB.Native.TableTest do
       assert schema.fields |> Enum.any?(&(&1.name == "types" and &1.nullable))
     end
 
+    test "it can dry run a cast before altering a column", %{table: fruits} do
+      to_int8 = ColumnAlteration.new("id", data_type: :int8)
+
+      assert {:ok, [%{path: "id", num_values: 2, num_failed: 1, num_overflowed: 1}]} ==
+               fruits |> Native.alter_column(to_int8, :dry_run)
+
+      {:ok, schema} = fruits |> Native.schema()
+      assert schema.fields |> Enum.any?(&(&1.name == "id" and &1.field_type == :int32))
+
+      {:ok, _} = fruits |> Native.alter_column(ColumnAlteration.new("id", data_type: :int16))
+      {:ok, schema} = fruits |> Native.schema()
+      assert schema.fields |> Enum.any?(&(&1.name == "id" and &1.field_type == :int16))
+
+      to_float64 = ColumnAlteration.new("avg_weight_oz", data_type: :float64)
+      {:ok, _} = fruits |> Native.alter_column(to_float64)
+      {:ok, rows} = fruits |> Native.query(QR.new() |> QR.select(["id", "avg_weight_oz"]))
+
+      assert rows == [
+               %{"id" => 123, "avg_weight_oz" => 5.363239765167236},
+               %{"id" => 456, "avg_weight_oz" => 4.334249973297119}
+             ]
+    end
+
+    test "it can dry run a cast of list values", %{conn: conn} do
+      schema = Schema.from([Field.list("counts", Field.int64("item"))])
+      big = 3_000_000_000
+      rows = [%{"counts" => [1, big]}, %{"counts" => [2]}, %{"counts" => [-big, 3]}]
+      {:ok, tbl} = conn |> Native.create_table_with_data("list_casts", rows, schema)
+      to_int32 = ColumnAlteration.new("counts", data_type: {:list, Field.int32("item")})
+
+      assert {:ok, [%{path: "counts", num_values: 3, num_failed: 2, num_overflowed: 2}]} ==
+               tbl |> Native.alter_column(to_int32, :dry_run)
+    end
+
+    test "it can write and read every field type", %{conn: conn} do
+      schema =
+        Schema.from([
+          Field.large_utf8("large"),
+          Field.binary("bytes"),
+          Field.large_binary("large_bytes"),
+          Field.int8("i8"),
+          Field.int16("i16"),
+          Field.int64("i64"),
+          Field.uint16("u16"),
+          Field.uint32("u32"),
+          Field.uint64("u64"),
+          Field.decimal128("price", 10, 2),
+          Field.timestamp("seen_at", :millisecond),
+          Field.struct("meta", [Field.int32("score"), Field.utf8("tag")]),
+          Field.list("flags", Field.boolean("item")),
+          Field.list("counts", Field.int64("item")),
+          Field.fixed_size_list("pair", Field.int64("item"), 2),
+          Field.multivector("tokens", Field.float64("item"), 2),
+          Field.multivector("half_tokens", Field.float16("item"), 2)
+        ])
+
+      row = %{
+        "large" => "large",
+        "bytes" => <<0, 255>>,
+        "large_bytes" => <<1, 2, 3>>,
+        "i8" => -5,
+        "i16" => -300,
+        "i64" => 1_099_511_627_776,
+        "u16" => 65_535,
+        "u32" => 4_000_000_000,
+        "u64" => 9_223_372_036_854_775_808,
+        "price" => "12.34",
+        "seen_at" => 1_735_726_210_000,
+        "meta" => %{"score" => 7, "tag" => "ripe"},
+        "flags" => [true, false],
+        "counts" => [1_099_511_627_776, -1],
+        "pair" => [3, 4],
+        "tokens" => [[0.1, 0.2], [0.3, 0.4]],
+        "half_tokens" => [[0.5, 0.25]]
+      }
+
+      {:ok, tbl} = conn |> Native.create_table_with_data("every_type", [row], schema)
+      assert {:ok, [row]} == tbl |> Native.query()
+
+      {:ok, read_schema} = tbl |> Native.schema()
+      field_types = &Enum.map(&1.fields, fn field -> field.field_type end)
+      assert field_types.(read_schema) == field_types.(schema)
+
+      {:error, {:invalid_input, msg}} =
+        conn |> Native.create_table_with_data("short_pair", [%{row | "pair" => [3]}], schema)
+
+      assert msg =~ "Expected 2 dimensions in pair, got 1"
+    end
+
+    test "it can alter nested struct fields", %{conn: conn} do
+      meta = Field.struct("meta", [Field.int32("score", nullable: false), Field.utf8("tag")])
+      {:ok, tbl} = conn |> Native.create_empty_table("nested", Schema.from([meta]))
+
+      {:ok, _} =
+        tbl
+        |> Native.alter_columns([
+          ColumnAlteration.new("meta.tag", rename: "label"),
+          ColumnAlteration.new("meta.score", nullable: true)
+        ])
+
+      {:ok, %Schema{fields: [%Field{field_type: {:struct, children}}]}} = tbl |> Native.schema()
+      assert children == [Field.int32("score"), Field.utf8("label")]
+
+      {:error, {:invalid_input, msg}} =
+        tbl |> Native.alter_column(ColumnAlteration.new("meta.score", data_type: :int64))
+
+      assert msg =~ "cannot cast nested field"
+    end
+
     test "it can add columns", %{table: fruits} do
       new_col = NewColumnTransform.all_nulls([Field.int32("qty")])
       fruits |> Native.add_columns(new_col)
@@ -80,6 +192,62 @@ defmodule ElixirNativeDB.Native.TableTest do
       {:ok, all_fruits} = fruits |> Native.query()
       assert all_fruits |> Enum.all?(&(&1["qty"] == 0))
     end
+
+    test "it can add columns from sql expressions and constants", %{table: fruits} do
+      upper = NewColumnTransform.sql_expressions([{"name_upper", "upper(name)"}])
+      {:ok, _} = fruits |> Native.add_columns(upper)
+      {:ok, _} = fruits |> Native.add_columns(NewColumnTransform.constant("stock", :int32, 12))
+      {:ok, schema} = fruits |> Native.schema()
+      assert schema.fields |> Enum.any?(&(&1.name == "stock" and &1.field_type == :int32))
+
+      {:ok, all_fruits} = fruits |> Native.query()
+      assert all_fruits |> Enum.all?(&(&1["name_upper"] == String.upcase(&1["name"])))
+      assert all_fruits |> Enum.all?(&(&1["stock"] == 12))
+
+      decimal = NewColumnTransform.constant("discount", {:decimal128, 5, 2}, "0.25")
+      {:ok, _} = fruits |> Native.add_columns(decimal)
+      {:ok, schema} = fruits |> Native.schema()
+      assert schema.fields |> Enum.any?(&(&1.field_type == {:decimal128, 5, 2}))
+    end
+
+    test "it can backfill columns through an elixir callback", %{table: fruits} do
+      dir = Path.join(File.cwd!(), "data/testing/udf_checkpoints")
+      name_length = &Enum.map(&1, fn row -> %{"name_length" => String.length(row["name"])} end)
+
+      udf =
+        NewColumnTransform.batch_udf(name_length, [Field.int32("name_length")],
+          input_columns: ["name"],
+          checkpoint_dir: dir
+        )
+
+      {:ok, _} = fruits |> Native.add_columns(udf)
+
+      {:ok, all_fruits} = fruits |> Native.query()
+      assert all_fruits |> Enum.all?(&(&1["name_length"] == String.length(&1["name"])))
+      assert File.ls!(dir) == []
+    end
+
+    test "it fails a backfill whose callback raises or whose checkpoints differ",
+         %{table: fruits} do
+      dir = Path.join(File.cwd!(), "data/testing/udf_stale_checkpoints")
+      File.rm_rf!(dir)
+      opts = [input_columns: ["name"], checkpoint_dir: dir]
+      raising = fn _rows -> raise "boom" end
+      failing = NewColumnTransform.batch_udf(raising, [Field.int32("n")], opts)
+
+      {:error, {_, msg}} = fruits |> Native.add_columns(failing)
+      assert msg =~ "boom"
+
+      other_fields = %{failing | output_schema: Schema.from([Field.utf8("n")])}
+      {:error, {:invalid_input, msg}} = fruits |> Native.add_columns(other_fields)
+      assert msg =~ "holds checkpoints of a backfill"
+
+      {:ok, _} = fruits |> Native.delete("id = 456")
+      {:error, {:invalid_input, msg}} = fruits |> Native.add_columns(failing)
+      assert msg =~ "holds checkpoints of a backfill of another table or table version"
+
+      File.rm_rf!(dir)
+    end
   end
 
   describe "Table :: CRUD ::" do
@@ -130,6 +298,44 @@ defmodule ElixirNativeDB.Native.TableTest do
       assert apple?["types"] == ["red", "green"]
     end
 
+    test "it can select columns and computed columns", %{table: fruits} do
+      {:ok, result} = fruits |> Native.query(QR.new() |> QR.select(["id", "name"]))
+      assert result == [%{"id" => 123, "name" => "apple"}, %{"id" => 456, "name" => "banana"}]
+
+      query =
+        QR.new()
+        |> QR.filter("id = 123")
+        |> QR.select(%{
+          "loud_name" => "upper(name)",
+          "double_id" => "id * 2",
+          "heavier" => "avg_weight_oz * 1.5"
+        })
+
+      assert {:ok, [result]} = fruits |> Native.query(query)
+
+      assert result == %{
+               "loud_name" => "APPLE",
+               "double_id" => 246,
+               "heavier" => 8.044859647750854
+             }
+    end
+
+    test "it can page through results with an offset", %{table: fruits} do
+      {:ok, [banana]} = fruits |> Native.query(QR.new() |> QR.limit(1) |> QR.offset(1))
+      assert banana["name"] == "banana"
+
+      assert {:ok, []} == fruits |> Native.query(QR.new() |> QR.offset(2))
+    end
+
+    test "it can return row ids", %{table: fruits} do
+      {:ok, [apple, banana]} = fruits |> Native.query(QR.new() |> QR.with_row_id())
+      assert apple["_rowid"] |> is_integer()
+      assert banana["_rowid"] > apple["_rowid"]
+
+      {:ok, [apple]} = fruits |> Native.query(QR.new() |> QR.filter("id = 123"))
+      refute apple |> Map.has_key?("_rowid")
+    end
+
     test "it can add rows to existing table", %{table: fruits} do
       fruits |> Native.add(new_fruits())
       {:ok, full_table} = fruits |> Native.query()
@@ -138,12 +344,25 @@ defmodule ElixirNativeDB.Native.TableTest do
       assert full_table |> Enum.find(fn fruit -> fruit["name"] == "orange" end)
     end
 
+    test "it can overwrite a table's rows with write parameters", %{table: fruits} do
+      cfg = AddConfig.new(mode: :overwrite, max_rows_per_file: 1, max_rows_per_group: 1)
+      {:ok, _} = fruits |> Native.add(new_fruits(), cfg)
+
+      {:ok, full_table} = fruits |> Native.query()
+      assert full_table |> Enum.map(& &1["name"]) |> Enum.sort() == ["grape", "orange"]
+
+      {:error, {:invalid_input, _}} =
+        fruits |> Native.add(new_fruits(), AddConfig.new(data_storage_version: "9.9"))
+    end
+
     test "it can update all rows in a table", %{table: fruits} do
       update_cfg =
         UpCfg.new()
         |> UpCfg.column("avg_weight_oz", "0")
 
-      fruits |> Native.update(update_cfg)
+      {:ok, %{num_updated_rows: 2, version: version}} = fruits |> Native.update(update_cfg)
+      {:ok, %{version: next_version}} = fruits |> Native.delete("id = 123")
+      assert next_version == version + 1
 
       {:ok, result} = fruits |> Native.query()
       assert result |> Enum.all?(&(&1["avg_weight_oz"] == 0.0))
@@ -181,6 +400,138 @@ defmodule ElixirNativeDB.Native.TableTest do
       assert results |> length() == 0
     end
 
+    test "it reports deleted rows and their version", %{table: fruits} do
+      {:ok, %{num_deleted_rows: 1, version: version, row_ids: [_id], rows: nil}} =
+        fruits |> Native.delete("id = 123", :row_ids)
+
+      {:ok, %{num_deleted_rows: 1, version: next_version, rows: [banana]}} =
+        fruits |> Native.delete("id = 456", :rows)
+
+      assert next_version == version + 1
+      assert banana["name"] == "banana"
+    end
+
+    test "it can return rows as they were before an update", %{table: fruits} do
+      update_cfg =
+        UpCfg.new()
+        |> UpCfg.column("name", "upper(name)")
+        |> UpCfg.filter("id = 123")
+        |> UpCfg.returning(:rows)
+
+      {:ok, %{num_updated_rows: 1, rows: [apple]}} = fruits |> Native.update(update_cfg)
+      assert apple["name"] == "apple"
+      assert {:ok, 1} == fruits |> Native.count_rows("name = 'APPLE'")
+    end
+
+    test "it can merge insert with column and by-source updates", %{table: fruits} do
+      [apple, _banana] = fruits()
+      apple = %{apple | "name" => "APPLE", "avg_weight_oz" => 1.0}
+      kiwi = %{apple | "id" => 789, "name" => "kiwi", "avg_weight_oz" => 2.0}
+      source = [apple, kiwi] |> Infer.clean()
+      by_source_update = [%ColumnOp{column: "name", operation: "'gone'"}]
+
+      cfg = %MergeInsertConfig{
+        on: ["id"],
+        when_matched_update_all: true,
+        when_matched_update_columns: ["avg_weight_oz"]
+      }
+
+      {:error, {:invalid_input, msg}} = fruits |> Native.merge_insert(source, cfg)
+      assert msg =~ "cannot be combined"
+
+      cfg = %MergeInsertConfig{on: ["id"], when_matched_update_columns: ["avg_weight_oz"]}
+      {:ok, result} = fruits |> Native.merge_insert([apple] |> Infer.clean(), cfg)
+      assert %{num_inserted_rows: 0, num_updated_rows: 1, num_deleted_rows: 0} = result
+      version = result.version
+
+      # insert new rows, update a column of matched ones and mark missing ones at once
+      cfg = %MergeInsertConfig{
+        on: ["id"],
+        when_matched_update_columns: ["avg_weight_oz"],
+        when_not_matched_insert_all: true,
+        when_not_matched_by_source_update: by_source_update
+      }
+
+      {:ok, result} = fruits |> Native.merge_insert(source, cfg)
+      assert %{num_inserted_rows: 1, num_updated_rows: 2, num_deleted_rows: 0} = result
+      assert result.version == version + 1
+
+      {:ok, rows} = fruits |> Native.query()
+      rows = rows |> Map.new(&{&1["id"], {&1["name"], &1["avg_weight_oz"]}})
+
+      assert rows == %{
+               123 => {"apple", 1.0},
+               456 => {"gone", 4.334249973297119},
+               789 => {"kiwi", 2.0}
+             }
+    end
+
+    test "it can reject or dedupe duplicate merge insert keys", %{table: fruits} do
+      [apple, _banana] = fruits()
+      source = [apple, %{apple | "name" => "red apple"}] |> Infer.clean()
+      cfg = %MergeInsertConfig{on: ["id"], when_matched_update_all: true}
+
+      {:error, {:invalid_input, msg}} = fruits |> Native.merge_insert(source, cfg)
+      assert msg =~ "1 duplicated keys on (id): (123)"
+
+      cfg = %MergeInsertConfig{cfg | duplicate_keys: :last_write_wins}
+      {:ok, %{num_updated_rows: 1}} = fruits |> Native.merge_insert(source, cfg)
+      assert {:ok, 1} == fruits |> Native.count_rows("name = 'red apple'")
+    end
+
+    test "it can bind values into filters", %{table: fruits} do
+      assert {:ok, 1} == fruits |> Native.count_rows(Filter.new("name = ?", ["apple"]))
+      assert {:ok, 0} == fruits |> Native.count_rows(Filter.new("name = ?", ["x' OR '1' = '1"]))
+      backslashed = Filter.new("name = ?", ["x\\' OR 1 = 1 --"])
+      assert {:ok, 0} == fruits |> Native.count_rows(backslashed)
+      assert {:ok, 1} == fruits |> Native.count_rows(Filter.new("id -? > 400", [-1]))
+      assert {:ok, 2} == fruits |> Native.count_rows(Filter.new("id > ?", [-1.5]))
+
+      commented = Filter.new("id = ? -- or ?\n/* ? */", [123])
+      assert {:ok, 1} == fruits |> Native.count_rows(commented)
+
+      in_list = Filter.new("name IN $names AND id > $id", names: ["apple", "banana"], id: 0)
+      {:ok, results} = fruits |> Native.query(QR.new() |> QR.filter(in_list))
+      assert results |> length() == 2
+
+      update_cfg =
+        UpCfg.new()
+        |> UpCfg.column("avg_weight_oz", "0.0")
+        |> UpCfg.filter(Filter.new("name = '?' OR name = ?", ["apple"]))
+
+      {:ok, %{num_updated_rows: 1, row_ids: nil, rows: nil}} = fruits |> Native.update(update_cfg)
+
+      fruits |> Native.delete(Filter.new("is_spheroid = ? AND id = ?", [true, 123]))
+      {:ok, [result]} = fruits |> Native.query()
+      assert result["name"] == "banana"
+
+      {:error, {:invalid_input, msg}} =
+        fruits |> Native.count_rows(Filter.new("name = ? AND id = ?", ["apple"]))
+
+      assert msg =~ "more placeholders"
+
+      paris = %{DateTime.utc_now() | time_zone: "Europe/Paris", zone_abbr: "CET"}
+
+      {:error, {:invalid_input, msg}} =
+        fruits |> Native.count_rows(Filter.new("created_at > ?", [paris]))
+
+      assert msg =~ "must be in UTC"
+    end
+
+    test "it can validate filters against the schema", %{table: fruits} do
+      assert {:ok, []} == fruits |> Native.validate_filter("name = 'apple' AND id > 100")
+      assert {:ok, []} == fruits |> Native.validate_filter(Filter.new("id IN ?", [[1, 2]]))
+
+      {:ok, [unknown, mismatch]} =
+        fruits |> Native.validate_filter("nme = 'apple' AND id = 'abc' AND is_spheroid")
+
+      assert %{columns: ["nme"], reason: :unknown_column} = unknown
+      assert %{columns: ["id"], reason: :type_mismatch} = mismatch
+
+      {:ok, [%{reason: :type_mismatch}]} = fruits |> Native.validate_filter("name")
+      {:ok, [%{reason: :syntax, columns: []}]} = fruits |> Native.validate_filter("name = ")
+    end
+
     test "it can work with URLs", %{conn: conn} do
       {:ok, urls} =
         conn
@@ -197,7 +548,7 @@ defmodule ElixirNativeDB.Native.TableTest do
         |> UpCfg.filter("id = 1")
         |> UpCfg.column("domain", "\"https://punch-and-pie.org\"")
 
-      {result2, num_updated} = urls |> Native.update(query)
+      {result2, %{num_updated_rows: num_updated}} = urls |> Native.update(query)
       assert result2 == :ok
       assert num_updated == 1
       query = QR.new() |> QR.filter("id = 1")
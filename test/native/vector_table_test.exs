@@ -2,6 +2,11 @@ defmodule ElixirLanceDB.Native.VectorTableTest do
   alias ElixirLanceDB.Native.Table.VectorQueryRequest, as: VQR
   alias ElixirLanceDB.Native
   alias ElixirLanceDB.Native.Table.Index
+  alias ElixirLanceDB.Native.Schema
+  alias ElixirLanceDB.Native.Schema.Field
+  alias ElixirLanceDB.Native.Table.QueryRequest
+  alias ElixirLanceDB.Native.Table.Reranker
+  alias ElixirLanceDB.Native.Table.SearchByExample
 
   use ExUnit.Case
 
@@ -10,7 +15,7 @@ defmodule ElixirLanceDB.Native.VectorTableTest do
     conn |> Native.drop_all_tables()
     conn |> Native.create_table("vectors", create_rows())
     {:ok, vectors} = conn |> Native.open_table("vectors")
-    %{table: vectors}
+    %{table: vectors, conn: conn}
   end
 
   describe "Vector Table :: Indices" do
@@ -32,6 +37,44 @@ defmodule ElixirLanceDB.Native.VectorTableTest do
       assert records |> Enum.all?(&is_map/1)
     end
 
+    test "it can search many query vectors at once", %{table: vectors} do
+      query_vectors = [create_vec(), create_vec(), create_vec()]
+      query = VQR.batch(query_vectors, base: QueryRequest.new() |> QueryRequest.limit(4))
+
+      {:ok, results} = vectors |> Native.batch_vector_search(query)
+      assert results |> length() == 3
+      assert results |> Enum.all?(&(length(&1) == 4))
+      refute results |> List.flatten() |> Enum.any?(&Map.has_key?(&1, "query_index"))
+
+      {:ok, single} = vectors |> Native.vector_search(VQR.new(Enum.at(query_vectors, 1)))
+      [second | _] = results |> Enum.at(1)
+      assert second["content"] == hd(single)["content"]
+    end
+
+    test "it can search using an existing row's vector", %{table: vectors} do
+      example = SearchByExample.new("vector", filter: "content = 'content for row 7'")
+      {:ok, result} = vectors |> Native.search_by_example(example, VQR.new([]))
+      assert result |> length() == 10
+      refute result |> Enum.any?(&(&1["content"] == "content for row 7"))
+
+      source_query =
+        QueryRequest.new()
+        |> QueryRequest.filter("content = 'content for row 7'")
+        |> QueryRequest.with_row_id()
+
+      {:ok, [source]} = vectors |> Native.query(source_query)
+
+      example = SearchByExample.new("vector", row_id: source["_rowid"], exclude_source: false)
+      {:ok, [first | _]} = vectors |> Native.search_by_example(example, VQR.new([]))
+      assert first["content"] == "content for row 7"
+      assert first["_distance"] == 0.0
+
+      ambiguous = SearchByExample.new("vector", filter: "content LIKE 'content%'")
+
+      assert {:error, {:invalid_input, _}} =
+               vectors |> Native.search_by_example(ambiguous, VQR.new([]))
+    end
+
     test "it can perform a hybrid query", %{table: vectors} do
       vectors |> Native.create_index(["content"], Index.fts())
 
@@ -45,6 +88,236 @@ defmodule ElixirLanceDB.Native.VectorTableTest do
       [first | _] = result
       assert first["content"] =~ "content for row"
     end
+
+    test "it can configure hybrid reranking", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+
+      query =
+        VQR.new(create_vec(), base: QueryRequest.new() |> QueryRequest.limit(5))
+        |> VQR.hybridize("row 56", "content",
+          reranker: Reranker.linear_combination(vector_weight: 0.0, fts_weight: 1.0),
+          norm: :rank,
+          vector_limit: 20,
+          fts_limit: 3
+        )
+
+      {:ok, result} = vectors |> Native.hybrid_search(query)
+      assert result |> length() == 5
+      [first | _] = result
+      assert first["content"] == "content for row 56"
+      assert first["_relevance_score"] == 1.0
+      refute first |> Map.has_key?("_rowid")
+
+      scores = result |> Enum.map(& &1["_relevance_score"])
+      assert scores == scores |> Enum.sort(:desc)
+
+      rrf = query |> Map.put(:reranker, Reranker.rrf(30))
+      {:ok, result} = vectors |> Native.hybrid_search(rrf)
+      assert result |> length() == 5
+
+      invalid = query |> Map.put(:reranker, Reranker.rrf(0))
+      assert {:error, {:invalid_input, _}} = vectors |> Native.hybrid_search(invalid)
+    end
+
+    test "it fetches each hybrid leg's own candidate count", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      parent = self()
+
+      reranker =
+        Reranker.callback(fn _query, rows ->
+          send(parent, {:candidates, rows})
+          rows |> Enum.map(fn _ -> 1.0 end)
+        end)
+
+      query =
+        VQR.new(create_vec(), base: QueryRequest.new() |> QueryRequest.limit(5))
+        |> VQR.hybridize("row", "content", reranker: reranker, vector_limit: 20, fts_limit: 3)
+
+      {:ok, result} = vectors |> Native.hybrid_search(query)
+      assert result |> length() == 5
+      assert_received {:candidates, rows}
+      assert rows |> Enum.count(& &1["_distance"]) == 20
+      assert rows |> length() in 20..23
+
+      wide = %{query | vector_limit: 2, fts_limit: 30}
+      {:ok, result} = vectors |> Native.hybrid_search(wide)
+      assert result |> length() == 5
+      assert_received {:candidates, rows}
+      assert rows |> Enum.count(& &1["_distance"]) == 2
+      assert rows |> length() in 30..32
+    end
+
+    test "it can rerank with an elixir callback", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+
+      # favours the highest row number among the candidates
+      reranker =
+        Reranker.callback(fn _query, rows ->
+          rows |> Enum.map(&(&1["content"] |> String.split() |> List.last() |> String.to_integer()))
+        end)
+
+      query =
+        VQR.new(create_vec(), base: QueryRequest.new() |> QueryRequest.limit(3))
+        |> VQR.hybridize("row 56", "content", reranker: reranker)
+
+      {:ok, result} = vectors |> Native.hybrid_search(query)
+      assert result |> length() == 3
+      scores = result |> Enum.map(& &1["_relevance_score"])
+      assert scores == scores |> Enum.sort(:desc)
+
+      {:ok, result} = vectors |> Native.vector_search(query |> Map.put(:vector_limit, 50))
+      assert result |> length() == 3
+      assert result |> Enum.all?(&Map.has_key?(&1, "_relevance_score"))
+    end
+
+    test "it times out when the callback does not reply", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      silent = spawn_link(fn -> Process.sleep(:infinity) end)
+
+      query =
+        VQR.new(create_vec())
+        |> VQR.hybridize("row 56", "content", reranker: Reranker.callback(silent, timeout: 50))
+
+      assert {:error, {:lance_timeout, _}} = vectors |> Native.hybrid_search(query)
+    end
+
+    test "it fails the search when the callback raises", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+      parent = self()
+
+      reranker =
+        Reranker.callback(fn query, _rows ->
+          send(parent, {:reranked, query})
+          raise "no model loaded"
+        end)
+
+      query =
+        VQR.new(create_vec())
+        |> VQR.hybridize("row 56", "content", reranker: reranker)
+
+      {:error, {:lance_runtime, msg}} = vectors |> Native.hybrid_search(query)
+      assert msg =~ "no model loaded"
+      assert_received {:reranked, "row 56"}
+
+      vector = %{VQR.new(create_vec()) | reranker: reranker}
+      {:error, {:lance_runtime, _}} = vectors |> Native.vector_search(vector)
+      assert_received {:reranked, ""}
+    end
+  end
+
+  describe "Vector Table :: Vector Types ::" do
+    test "it can store and search binary vectors with hamming distance", %{conn: conn} do
+      schema =
+        Schema.from([
+          Field.utf8("name"),
+          Field.fixed_size_list("bits", Field.uint8("item"), 4)
+        ])
+
+      rows = [
+        %{"name" => "zeros", "bits" => [0, 0, 0, 0]},
+        %{"name" => "ones", "bits" => [255, 255, 255, 255]},
+        %{"name" => "mostly_zeros", "bits" => [1, 0, 0, 0]}
+      ]
+
+      {:ok, binary} = conn |> Native.create_table_with_data("binary", rows, schema)
+
+      query = VQR.new([0, 0, 0, 0], vector_type: :uint8, distance_type: :hamming)
+      {:ok, [first, second, third]} = binary |> Native.vector_search(query)
+
+      assert {first["name"], second["name"], third["name"]} == {"zeros", "mostly_zeros", "ones"}
+      assert first["bits"] == [0, 0, 0, 0]
+      assert third["_distance"] == 32.0
+    end
+
+    test "it can store and search float16 and float64 vectors", %{conn: conn} do
+      schema =
+        Schema.from([
+          Field.utf8("name"),
+          Field.float16("score"),
+          Field.float64("weight"),
+          Field.uint8("flags"),
+          Field.fixed_size_list("half", Field.float16("item"), 2),
+          Field.fixed_size_list("double", Field.float64("item"), 2)
+        ])
+
+      rows = [
+        %{"name" => "a", "score" => 0.5, "weight" => 0.1, "flags" => 3}
+        |> Map.merge(%{"half" => [0.5, 1.0], "double" => [0.25, 1.0]}),
+        %{"name" => "b", "score" => 1.5, "weight" => 0.2, "flags" => 255}
+        |> Map.merge(%{"half" => [-1.0, 2.0], "double" => [-1.0, 2.0]})
+      ]
+
+      {:ok, table} = conn |> Native.create_table_with_data("floats", rows, schema)
+
+      query = VQR.new([0.5, 1], column: "half", vector_type: :float16)
+      {:ok, [first | _]} = table |> Native.vector_search(query)
+      assert first["name"] == "a"
+      assert first["half"] == [0.5, 1.0]
+
+      query = VQR.new([-1, 2], column: "double", vector_type: :float64)
+      {:ok, [first | _]} = table |> Native.vector_search(query)
+      assert first["name"] == "b"
+      assert first["double"] == [-1.0, 2.0]
+      assert {first["score"], first["weight"], first["flags"]} == {1.5, 0.2, 255}
+    end
+
+    test "it can index and search multivector columns", %{conn: conn} do
+      schema =
+        Schema.from([
+          Field.utf8("name"),
+          Field.multivector("tokens", Field.float32("item"), 16)
+        ])
+
+      rows =
+        0..299
+        |> Enum.map(fn n ->
+          %{"name" => "doc #{n}", "tokens" => [create_vec(), create_vec(), create_vec()]}
+        end)
+
+      {:ok, table} = conn |> Native.create_table_with_data("multivectors", rows, schema)
+
+      index = Index.ivf_pq(distance_type: :cosine, num_partitions: 2)
+      {result, _} = table |> Native.create_index(["tokens"], index)
+
+      assert result == :ok
+
+      %{"tokens" => [token | _] = tokens} = Enum.at(rows, 42)
+      assert tokens |> length() == 3
+
+      query = VQR.new([token], column: "tokens", distance_type: :cosine, use_index: false)
+      {:ok, [first | _] = results} = table |> Native.vector_search(query)
+      assert results |> length() == 10
+      assert first["name"] == "doc 42"
+      assert first["tokens"] |> length() == 3
+      assert first["tokens"] |> Enum.all?(&(length(&1) == 16))
+
+      {:ok, indexed} = table |> Native.vector_search(%VQR{query | use_index: true})
+      assert indexed |> length() == 10
+    end
+  end
+
+  describe "Vector Table :: Query Plans ::" do
+    test "it can explain plain, vector and hybrid plans", %{table: vectors} do
+      vectors |> Native.create_index(["content"], Index.fts())
+
+      {:ok, plan} = vectors |> Native.explain_plan(QueryRequest.new() |> QueryRequest.limit(5))
+      assert plan =~ "Projection"
+
+      vector = VQR.new(create_vec()) |> VQR.filter("content LIKE '%row 1%'")
+      {:ok, plan} = vectors |> Native.explain_plan(vector, true)
+      assert plan =~ "KNN"
+
+      hybrid = vector |> VQR.hybridize("row 56", "content")
+      {:ok, plan} = vectors |> Native.explain_plan(hybrid)
+      assert plan =~ "Vector search:"
+      assert plan =~ "Full text search:"
+      assert plan =~ "MatchQuery"
+    end
+
+    test "it can analyze a plan with runtime metrics", %{table: vectors} do
+      {:ok, plan} = vectors |> Native.analyze_plan(VQR.new(create_vec()))
+      assert plan =~ "metrics"
+    end
   end
 
   defp create_rows(num \\ 256, dim_times_8 \\ 2) when is_integer(num) do